@@ -6,21 +6,79 @@ use anchor_spl::{
 
 declare_id!("6dktB5XDeCN2Gw91Ux8NJSS3L6b7htwBUhGkS7TLC4bJ");
 
+const BPS_DENOM: u128 = 10_000;
+const SECONDS_PER_YEAR: u128 = 365 * 86_400;
+const REWARD_PRECISION: u128 = 1_000_000_000_000; // 1e12 scale for reward accounting
+const STAKE_CHECKPOINTS: usize = 8; // Balance history kept per StakeRec for vote snapshots
+const MAX_LEVERAGE_BPS: u32 = 10_000; // 1x: active cover never exceeds staked capital
+const INDEX_PRECISION: u128 = 1_000_000_000_000; // 1e12 scale for Config.stake_index
+const MAX_COVER_DURATION: i64 = 365 * 86_400;
+const REWARD_BUCKET: i64 = 7 * 86_400; // Premium streams only end on these boundaries
+const REWARD_BUCKETS: usize = (MAX_COVER_DURATION / REWARD_BUCKET) as usize + 2; // Ring spans the longest cover
+
+// Config.paused bits
+pub const PAUSE_STAKING: u8 = 1 << 0; // stake, unstake, withdraw, claim rewards
+pub const PAUSE_COVER:   u8 = 1 << 1; // buy_cover
+pub const PAUSE_CLAIMS:  u8 = 1 << 2; // create_claim, vote, resolve
+const PAUSE_ALL:         u8 = PAUSE_STAKING | PAUSE_COVER | PAUSE_CLAIMS;
+
 #[program]
 pub mod actuary {
     use super::*;
 
     // ─── Initialize: set USDC mint & admin ───────────────────────────────
-    pub fn initialize(ctx: Context<Initialize>, admin: Pubkey) -> Result<()> {
+    pub fn initialize(
+        ctx: Context<Initialize>,
+        admin: Pubkey,
+        unstake_cooldown: i64,
+        leverage_bps: u32,
+        voting_period: i64,
+        quorum_bps: u16,
+    ) -> Result<()> {
+        require!(unstake_cooldown >= 0, InsuranceError::InvalidConfig);
+        require!(
+            leverage_bps > 0 && leverage_bps <= MAX_LEVERAGE_BPS,
+            InsuranceError::InvalidConfig
+        );
+        require!(voting_period > 0, InsuranceError::InvalidConfig);
+        require!(quorum_bps as u128 <= BPS_DENOM, InsuranceError::InvalidConfig);
         let cfg = &mut ctx.accounts.config;
-        cfg.admin      = admin;
-        cfg.usdc_mint  = ctx.accounts.usdc_mint.key();
-        cfg.bump       = ctx.bumps.config;
+        cfg.admin       = admin;
+        cfg.underwriter = admin;
+        cfg.pauser      = admin;
+        cfg.usdc_mint   = ctx.accounts.usdc_mint.key();
+        cfg.bump        = ctx.bumps.config;
+        cfg.unstake_cooldown   = unstake_cooldown;
+        cfg.total_staked       = 0;
+        cfg.total_shares       = 0;
+        cfg.pending_shares     = 0;
+        cfg.stake_index        = INDEX_PRECISION;
+        cfg.stake_epoch        = 0;
+        cfg.total_active_cover = 0;
+        cfg.leverage_bps       = leverage_bps;
+        cfg.voting_period      = voting_period;
+        cfg.quorum_bps         = quorum_bps;
+        cfg.paused             = 0;
+        cfg.pending_admin      = Pubkey::default();
+
+        let pool = &mut ctx.accounts.pool;
+        pool.last_update_ts = Clock::get()?.unix_timestamp;
+        pool.bump           = ctx.bumps.pool;
+
+        emit!(ConfigInitialized {
+            admin,
+            usdc_mint: cfg.usdc_mint,
+            unstake_cooldown,
+            leverage_bps,
+            voting_period,
+            quorum_bps,
+        });
         Ok(())
     }
 
     // ─── Stake: lock USDC into the pool vault ────────────────────────────
     pub fn stake(ctx: Context<Stake>, amount: u64) -> Result<()> {
+        require_unpaused(&ctx.accounts.config, PAUSE_STAKING)?;
         // Transfer USDC from user → pool vault via CPI :contentReference[oaicite:1]{index=1}
         token::transfer(
             CpiContext::new(
@@ -34,18 +92,169 @@ pub mod actuary {
             amount,
         )?;
 
-        // Record stake in PDA
+        // Bring rewards up to date before the stake changes
+        let cfg = &mut ctx.accounts.config;
+        let pool = &mut ctx.accounts.pool;
+        accrue_rewards(pool, cfg.total_shares, Clock::get()?.unix_timestamp)?;
+
+        // An emptied pool starts over at par
+        if cfg.total_shares == 0 && cfg.pending_shares == 0 {
+            cfg.stake_index = INDEX_PRECISION;
+        }
+        // Record stake in PDA as shares at the current index
+        let minted = shares_for(amount, cfg.stake_index, false)?;
         let rec = &mut ctx.accounts.stake_rec;
+        sync_stake_epoch(rec, cfg, pool)?;
+        settle_rewards(rec, pool)?;
         rec.staker = ctx.accounts.staker.key();
-        rec.amount = rec.amount.checked_add(amount).unwrap();
+        rec.shares = rec.shares.checked_add(minted).unwrap();
         rec.bump = ctx.bumps.stake_rec;
+        rec.reward_debt = reward_debt(rec.shares, pool)?;
+        let staker_total = shares_value(rec.shares, cfg.stake_index)?;
+        checkpoint_stake(rec, staker_total, Clock::get()?.slot);
+
+        cfg.total_shares = cfg.total_shares.checked_add(minted).unwrap();
+        cfg.total_staked = shares_value(cfg.total_shares, cfg.stake_index)?;
+
+        emit!(StakeEvent {
+            staker: rec.staker,
+            amount,
+            staker_total,
+            total_staked: cfg.total_staked,
+        });
+        Ok(())
+    }
+
+    // ─── RequestUnstake: move stake into cooldown ────────────────────────
+    pub fn request_unstake(ctx: Context<RequestUnstake>, amount: u64) -> Result<()> {
+        require_unpaused(&ctx.accounts.config, PAUSE_STAKING)?;
+        let cfg = &mut ctx.accounts.config;
+        let rec = &mut ctx.accounts.stake_rec;
+        let now = Clock::get()?.unix_timestamp;
+        let pool = &mut ctx.accounts.pool;
+        accrue_rewards(pool, cfg.total_shares, now)?;
+        sync_stake_epoch(rec, cfg, pool)?;
+        let value = shares_value(rec.shares, cfg.stake_index)?;
+        require!(amount > 0 && amount <= value, InsuranceError::InsufficientStake);
+        // Stake backing a vote on an unresolved claim stays put
+        require!(rec.open_votes == 0, InsuranceError::StakeLockedByVote);
+        // Move shares rounded up into cooldown, so rounding favours the pool
+        let burned = shares_for(amount, cfg.stake_index, true)?;
+        let remaining_shares = cfg.total_shares.checked_sub(burned).unwrap();
+        // Remaining stake must still cover everything that is sold
+        let remaining = shares_value(remaining_shares, cfg.stake_index)?;
+        require!(
+            cover_capacity(remaining, cfg.leverage_bps) >= cfg.total_active_cover as u128,
+            InsuranceError::InsufficientCapacity
+        );
+
+        settle_rewards(rec, pool)?;
+        rec.shares = rec.shares.checked_sub(burned).unwrap();
+        rec.reward_debt = reward_debt(rec.shares, pool)?;
+        let staker_total = shares_value(rec.shares, cfg.stake_index)?;
+        checkpoint_stake(rec, staker_total, Clock::get()?.slot);
+        // Shares in cooldown stop earning premium but still absorb losses
+        rec.pending_shares = rec.pending_shares.checked_add(burned).unwrap();
+        rec.unstake_ready_ts = now.checked_add(cfg.unstake_cooldown).unwrap();
+        cfg.total_shares = remaining_shares;
+        cfg.pending_shares = cfg.pending_shares.checked_add(burned).unwrap();
+        cfg.total_staked = remaining;
+
+        emit!(UnstakeRequested {
+            staker: rec.staker,
+            amount,
+            pending_unstake: shares_value(rec.pending_shares, cfg.stake_index)?,
+            ready_ts: rec.unstake_ready_ts,
+            total_staked: cfg.total_staked,
+        });
+        Ok(())
+    }
+
+    // ─── WithdrawStake: pay out stake once cooldown has elapsed ──────────
+    pub fn withdraw_stake(ctx: Context<WithdrawStake>) -> Result<()> {
+        require_unpaused(&ctx.accounts.config, PAUSE_STAKING)?;
+        let cfg = &mut ctx.accounts.config;
+        let rec = &mut ctx.accounts.stake_rec;
+        sync_stake_epoch(rec, cfg, &ctx.accounts.pool)?;
+        require!(rec.pending_shares > 0, InsuranceError::NothingToWithdraw);
+        require!(
+            Clock::get()?.unix_timestamp >= rec.unstake_ready_ts,
+            InsuranceError::CooldownActive
+        );
+        // Valued now, so losses during the cooldown are shared
+        let amount = shares_value(rec.pending_shares, cfg.stake_index)?;
+        cfg.pending_shares = cfg.pending_shares.checked_sub(rec.pending_shares).unwrap();
+        rec.pending_shares = 0;
+        let staker = rec.staker;
+
+        // Transfer USDC from vault → staker ATA, signed by the config PDA
+        let bump = [ctx.accounts.config.bump];
+        let signer: &[&[&[u8]]] = &[&[b"config", &bump]];
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from:      ctx.accounts.pool_vault.to_account_info(),
+                    to:        ctx.accounts.staker_ata.to_account_info(),
+                    authority: ctx.accounts.pool_authority.clone(),
+                },
+                signer,
+            ),
+            amount,
+        )?;
+
+        emit!(StakeWithdrawn { staker, amount });
+        Ok(())
+    }
+
+    // ─── ClaimRewards: pay out streamed premium share ────────────────────
+    pub fn claim_rewards(ctx: Context<ClaimRewards>) -> Result<()> {
+        require_unpaused(&ctx.accounts.config, PAUSE_STAKING)?;
+        let pool = &mut ctx.accounts.pool;
+        accrue_rewards(pool, ctx.accounts.config.total_shares, Clock::get()?.unix_timestamp)?;
+        let rec = &mut ctx.accounts.stake_rec;
+        sync_stake_epoch(rec, &ctx.accounts.config, pool)?;
+        settle_rewards(rec, pool)?;
+        rec.reward_debt = reward_debt(rec.shares, pool)?;
+        let amount = rec.pending_rewards;
+        require!(amount > 0, InsuranceError::NothingToWithdraw);
+        rec.pending_rewards = 0;
+        let staker = rec.staker;
+
+        // Transfer USDC from vault → staker ATA, signed by the config PDA
+        let bump = [ctx.accounts.config.bump];
+        let signer: &[&[&[u8]]] = &[&[b"config", &bump]];
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from:      ctx.accounts.pool_vault.to_account_info(),
+                    to:        ctx.accounts.staker_ata.to_account_info(),
+                    authority: ctx.accounts.pool_authority.clone(),
+                },
+                signer,
+            ),
+            amount,
+        )?;
+
+        emit!(RewardsClaimed { staker, amount });
         Ok(())
     }
 
     // ─── BuyCover: pay premium, get coverage ──────────────────────────
-    pub fn buy_cover(ctx: Context<BuyCover>, cover_id: u64, amount: u64, premium: u64, duration: i64) -> Result<()> {
-        // Validate cover type exists
+    pub fn buy_cover(ctx: Context<BuyCover>, cover_id: u64, amount: u64, duration: i64) -> Result<()> {
+        require_unpaused(&ctx.accounts.config, PAUSE_COVER)?;
+        // Validate cover type exists and price the request on-chain
         let cover_type = &ctx.accounts.cover_type;
+        let premium = quote_premium(&cover_type.pricing, amount, duration)?;
+
+        // Active cover may never exceed staked capital times leverage
+        let cfg = &ctx.accounts.config;
+        let new_active = cfg.total_active_cover.checked_add(amount).unwrap();
+        require!(
+            new_active as u128 <= cover_capacity(cfg.total_staked, cfg.leverage_bps),
+            InsuranceError::InsufficientCapacity
+        );
         // Transfer premium from claimant to pool vault
         token::transfer(
             CpiContext::new(
@@ -59,6 +268,10 @@ pub mod actuary {
             premium,
         )?;
 
+        // Stream the premium to stakers over the cover's lifetime
+        let now = Clock::get()?.unix_timestamp;
+        let rate = start_premium_stream(&mut ctx.accounts.pool, premium, now + duration, cfg.total_shares, now)?;
+
         // Create Cover account
         let cover = &mut ctx.accounts.cover;
         cover.claimant = ctx.accounts.claimant.key();
@@ -66,116 +279,733 @@ pub mod actuary {
         cover.cover_name = cover_type.name;
         cover.amount = amount;
         cover.premium_paid = premium;
-        cover.start_ts = Clock::get()?.unix_timestamp;
+        cover.start_ts = now;
         cover.duration = duration;
         cover.active = true;
         cover.bump = ctx.bumps.cover;
+        cover.reward_rate = rate;
+        cover.open_claims = 0;
+
+        ctx.accounts.config.total_active_cover = new_active;
+        let cover_type = &mut ctx.accounts.cover_type;
+        cover_type.active_cover = cover_type.active_cover.checked_add(amount).unwrap();
 
         // Track cover_id in UserCovers
         let user_covers = &mut ctx.accounts.user_covers;
         if !user_covers.cover_ids.contains(&cover_id) {
             user_covers.cover_ids.push(cover_id);
         }
+
+        emit!(CoverPurchased {
+            claimant: ctx.accounts.claimant.key(),
+            cover_id,
+            amount,
+            premium,
+            start_ts: now,
+            duration,
+            total_active_cover: new_active,
+        });
+        Ok(())
+    }
+
+    // ─── ExpireCover: release capacity held by a lapsed cover ────────────
+    pub fn expire_cover(ctx: Context<ExpireCover>, _cover_id: u64) -> Result<()> {
+        let cover = &mut ctx.accounts.cover;
+        let now = Clock::get()?.unix_timestamp;
+        require!(cover.active, InsuranceError::NoActiveCover);
+        require!(now > cover.start_ts + cover.duration, InsuranceError::CoverNotExpired);
+        // A claim filed before the end must still be payable when it resolves
+        require!(cover.open_claims == 0, InsuranceError::ClaimsPending);
+        cover.active = false;
+
+        let cfg = &mut ctx.accounts.config;
+        stop_premium_stream(&mut ctx.accounts.pool, cover, cfg.total_shares, now)?;
+        cfg.total_active_cover = cfg.total_active_cover.checked_sub(cover.amount).unwrap();
+        let cover_type = &mut ctx.accounts.cover_type;
+        cover_type.active_cover = cover_type.active_cover.checked_sub(cover.amount).unwrap();
+
+        emit!(CoverLapsed {
+            claimant: cover.claimant,
+            cover_id: cover.cover_id,
+            amount: cover.amount,
+            total_active_cover: cfg.total_active_cover,
+        });
         Ok(())
     }
 
     // ─── CreateClaim: only if valid Cover ─────────────────────────────
-    pub fn create_claim(ctx: Context<CreateClaim>, cover_id: u64) -> Result<()> {
+    pub fn create_claim(
+        ctx: Context<CreateClaim>,
+        cover_id: u64,
+        requested_amount: u64,
+        evidence_hash: [u8; 32],
+    ) -> Result<()> {
+        require_unpaused(&ctx.accounts.config, PAUSE_CLAIMS)?;
         let cover = &mut ctx.accounts.cover;
         let now = Clock::get()?.unix_timestamp;
         require!(cover.active, InsuranceError::NoActiveCover);
         require!(now >= cover.start_ts, InsuranceError::CoverNotStarted);
         require!(now <= cover.start_ts + cover.duration, InsuranceError::CoverExpired);
+        require!(
+            requested_amount > 0 && requested_amount <= cover.amount,
+            InsuranceError::InvalidClaimAmount
+        );
+        let claim_index = cover.claims_filed;
+        cover.claims_filed = cover.claims_filed.checked_add(1).unwrap();
+        cover.open_claims  = cover.open_claims.checked_add(1).unwrap();
+
+        // Quorum is fixed against the stake present when the claim is filed
+        let cfg = &ctx.accounts.config;
+        let quorum = ((cfg.total_staked as u128) * (cfg.quorum_bps as u128)).div_ceil(BPS_DENOM);
 
         let claim = &mut ctx.accounts.claim;
         claim.claimant = ctx.accounts.claimant.key();
+        claim.cover_id = cover_id;
+        claim.claim_index = claim_index;
+        claim.requested_amount = requested_amount;
+        claim.evidence_hash    = evidence_hash;
         claim.yes      = 0;
         claim.no       = 0;
         claim.bump     = ctx.bumps.claim;
+        claim.status   = ClaimStatus::Open;
+        claim.created_ts      = now;
+        claim.created_slot    = Clock::get()?.slot;
+        claim.voting_deadline = now.checked_add(cfg.voting_period).unwrap();
+        claim.quorum          = quorum as u64;
+
+        emit!(ClaimCreated {
+            claim: claim.key(),
+            claimant: claim.claimant,
+            cover_id,
+            claim_index,
+            requested_amount,
+            evidence_hash,
+            voting_deadline: claim.voting_deadline,
+            quorum: claim.quorum,
+        });
         Ok(())
     }
 
-    // ─── Vote: yes/no weighted by staked USDC ────────────────────────────
-    pub fn vote(ctx: Context<Vote>, approve: bool) -> Result<()> {
-        // Ensure staker has a record
-        let stake_rec = &ctx.accounts.stake_rec;
-        require!(stake_rec.amount > 0, InsuranceError::NoStake);
+    // ─── Vote: yes/no weighted by USDC staked before the claim ───────────
+    // A yes vote may approve less than requested; `None` approves it in full.
+    pub fn vote(ctx: Context<Vote>, approve: bool, approved_amount: Option<u64>) -> Result<()> {
+        require_unpaused(&ctx.accounts.config, PAUSE_CLAIMS)?;
+        let stake_rec = &mut ctx.accounts.stake_rec;
+        let claim = &mut ctx.accounts.claim;
+        require!(claim.status == ClaimStatus::Open, InsuranceError::ClaimClosed);
+        require!(
+            Clock::get()?.unix_timestamp <= claim.voting_deadline,
+            InsuranceError::VotingClosed
+        );
+
+        // Weight is the stake held before the claim was filed, capped by what
+        // is still staked, so topping up or unstaking around a vote gains nothing
+        let staked = staked_value(stake_rec, &ctx.accounts.config)?;
+        let weight = stake_at(stake_rec, claim.created_slot).min(staked);
+        require!(weight > 0, InsuranceError::NoStake);
 
         // Prevent double-vote
         let vr = &mut ctx.accounts.vote_rec;
         require!(!vr.voted, InsuranceError::AlreadyVoted);
 
         // Tally vote
-        let claim = &mut ctx.accounts.claim;
+        let amount = approved_amount
+            .unwrap_or(claim.requested_amount)
+            .min(claim.requested_amount);
         if approve {
-            claim.yes = claim.yes.checked_add(stake_rec.amount).unwrap();
+            claim.yes = claim.yes.checked_add(weight).unwrap();
+            claim.approved_weighted = claim
+                .approved_weighted
+                .checked_add((weight as u128) * (amount as u128))
+                .ok_or(InsuranceError::MathOverflow)?;
         } else {
-            claim.no  = claim.no.checked_add(stake_rec.amount).unwrap();
+            claim.no  = claim.no.checked_add(weight).unwrap();
         }
         vr.voted   = true;
         vr.bump    = ctx.bumps.vote_rec;
+        // Lock the stake until the claim is settled
+        stake_rec.open_votes = stake_rec.open_votes.checked_add(1).unwrap();
+
+        emit!(VoteCast {
+            claim: claim.key(),
+            staker: stake_rec.staker,
+            approve,
+            weight,
+            approved_amount: if approve { amount } else { 0 },
+            yes: claim.yes,
+            no: claim.no,
+        });
         Ok(())
     }
 
-    // ─── Resolve: payout limited to cover amount ──────────────────────
-    pub fn resolve(ctx: Context<Resolve>, cover_id: u64) -> Result<()> {
+    // ─── ReleaseVote: unlock stake once voting on the claim is over ──────
+    pub fn release_vote(ctx: Context<ReleaseVote>) -> Result<()> {
+        // Tallies are frozen after the deadline, even if the claim never settles
         let claim = &ctx.accounts.claim;
+        require!(
+            claim.status != ClaimStatus::Open
+                || Clock::get()?.unix_timestamp > claim.voting_deadline,
+            InsuranceError::ClaimStillOpen
+        );
+        require!(ctx.accounts.vote_rec.voted, InsuranceError::NoVote);
+        let rec = &mut ctx.accounts.stake_rec;
+        rec.open_votes = rec.open_votes.checked_sub(1).unwrap();
+
+        emit!(VoteReleased {
+            claim: claim.key(),
+            staker: rec.staker,
+            open_votes: rec.open_votes,
+        });
+        Ok(())
+    }
+
+    // ─── Resolve: pay the stake-weighted approved amount ──────────────
+    pub fn resolve(ctx: Context<Resolve>, _cover_id: u64) -> Result<()> {
+        require_unpaused(&ctx.accounts.config, PAUSE_CLAIMS)?;
+        let claim = &mut ctx.accounts.claim;
+        require!(claim.status == ClaimStatus::Open, InsuranceError::ClaimClosed);
+        require!(
+            Clock::get()?.unix_timestamp > claim.voting_deadline,
+            InsuranceError::VotingStillOpen
+        );
+        // Settled either way, so the cover may expire once no claim is open
         let cover = &mut ctx.accounts.cover;
-        require!(claim.yes > claim.no, InsuranceError::ClaimDenied);
-        require!(cover.active, InsuranceError::NoActiveCover);
-        // Deactivate cover after claim
-        cover.active = false;
-        // Transfer USDC from vault → claimant ATA, up to cover amount
-        token::transfer(
-            CpiContext::new(
-                ctx.accounts.token_program.to_account_info(),
-                Transfer {
-                    from:      ctx.accounts.pool_vault.to_account_info(),
-                    to:        ctx.accounts.claimant_ata.to_account_info(),
-                    authority: ctx.accounts.pool_authority.clone(),
-                },
-            ),
-            cover.amount,
-        )?;
+        cover.open_claims = cover.open_claims.checked_sub(1).unwrap();
+        // A rejected claim, or one that missed quorum, is settled as Denied
+        // rather than rejected: votes close at the deadline, so an inquorate
+        // claim could never resolve and would lock its voters' stake and the
+        // cover forever
+        let quorum_met = claim.yes.checked_add(claim.no).unwrap() >= claim.quorum;
+        if !quorum_met || claim.yes <= claim.no {
+            claim.status = ClaimStatus::Denied;
+            emit!(ClaimResolved {
+                claim: claim.key(),
+                claimant: claim.claimant,
+                cover_id: claim.cover_id,
+                approved: false,
+                yes: claim.yes,
+                no: claim.no,
+                payout: 0,
+                remaining_cover: ctx.accounts.cover.amount,
+            });
+            return Ok(());
+        }
+        claim.status = ClaimStatus::Approved;
+
+        // Stake-weighted mean of the amounts approved by yes voters, limited
+        // to whatever cover is left. Earlier claims may already have paid the
+        // cover out in full; the claim still settles, just with nothing to pay
+        let approved = (claim.approved_weighted / claim.yes as u128) as u64;
+        let payout = if cover.active { approved.min(cover.amount) } else { 0 };
+        claim.payout = payout;
+
+        if payout > 0 {
+            cover.amount = cover.amount.checked_sub(payout).unwrap();
+            let cfg = &mut ctx.accounts.config;
+            let now = Clock::get()?.unix_timestamp;
+            if cover.amount == 0 {
+                // Deactivate cover once it is fully paid out
+                cover.active = false;
+                stop_premium_stream(&mut ctx.accounts.pool, cover, cfg.total_shares, now)?;
+            }
+            cfg.total_active_cover = cfg.total_active_cover.checked_sub(payout).unwrap();
+            // The payout leaves the vault, so every stake is written down pro rata
+            apply_loss(cfg, &mut ctx.accounts.pool, payout, now)?;
+            emit!(StakeWrittenDown {
+                loss: payout,
+                stake_index: cfg.stake_index,
+                total_staked: cfg.total_staked,
+            });
+            let cover_type = &mut ctx.accounts.cover_type;
+            cover_type.active_cover = cover_type.active_cover.checked_sub(payout).unwrap();
+
+            // Transfer USDC from vault → claimant ATA
+            let bump = [cfg.bump];
+            let signer: &[&[&[u8]]] = &[&[b"config", &bump]];
+            token::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from:      ctx.accounts.pool_vault.to_account_info(),
+                        to:        ctx.accounts.claimant_ata.to_account_info(),
+                        authority: ctx.accounts.pool_authority.clone(),
+                    },
+                    signer,
+                ),
+                payout,
+            )?;
+        }
+
+        let claim = &ctx.accounts.claim;
+        emit!(ClaimResolved {
+            claim: claim.key(),
+            claimant: claim.claimant,
+            cover_id: claim.cover_id,
+            approved: true,
+            yes: claim.yes,
+            no: claim.no,
+            payout,
+            remaining_cover: ctx.accounts.cover.amount,
+        });
         Ok(())
     }
 
-    pub fn add_cover_type(ctx: Context<AddCoverType>, cover_id: u64, name: [u8; 32]) -> Result<()> {
+    pub fn add_cover_type(
+        ctx: Context<AddCoverType>,
+        cover_id: u64,
+        name: [u8; 32],
+        pricing: CoverPricing,
+    ) -> Result<()> {
+        validate_pricing(&pricing)?;
         let cover_type = &mut ctx.accounts.cover_type;
         cover_type.cover_id = cover_id;
         cover_type.name = name;
+        cover_type.pricing = pricing;
         cover_type.bump = ctx.bumps.cover_type;
+
+        emit!(CoverTypeAdded { cover_id, name, pricing });
+        Ok(())
+    }
+
+    // ─── UpdateCoverType: re-price a cover type (admin or underwriter) ───
+    pub fn update_cover_type(
+        ctx: Context<UpdateCoverType>,
+        _cover_id: u64,
+        pricing: CoverPricing,
+    ) -> Result<()> {
+        validate_pricing(&pricing)?;
+        let cover_type = &mut ctx.accounts.cover_type;
+        cover_type.pricing = pricing;
+
+        emit!(CoverTypeUpdated {
+            cover_id: cover_type.cover_id,
+            pricing,
+            updated_by: ctx.accounts.signer.key(),
+        });
+        Ok(())
+    }
+
+    pub fn remove_cover_type(ctx: Context<RemoveCoverType>, _cover_id: u64) -> Result<()> {
+        // Only close once no cover sold under this type is still live
+        require!(ctx.accounts.cover_type.active_cover == 0, InsuranceError::CoverTypeInUse);
+
+        emit!(CoverTypeRemoved { cover_id: ctx.accounts.cover_type.cover_id });
+        Ok(())
+    }
+
+    // ─── SetRoles: admin assigns the underwriter and pauser keys ─────────
+    pub fn set_roles(ctx: Context<SetRoles>, underwriter: Pubkey, pauser: Pubkey) -> Result<()> {
+        let cfg = &mut ctx.accounts.config;
+        cfg.underwriter = underwriter;
+        cfg.pauser      = pauser;
+
+        emit!(RolesUpdated { underwriter, pauser });
+        Ok(())
+    }
+
+    // ─── Pause: pauser or admin halts operations immediately ─────────────
+    pub fn pause(ctx: Context<Pause>, flags: u8) -> Result<()> {
+        require!(flags != 0 && flags & !PAUSE_ALL == 0, InsuranceError::InvalidConfig);
+        let cfg = &mut ctx.accounts.config;
+        cfg.paused |= flags;
+
+        emit!(PauseUpdated { paused: cfg.paused, by: ctx.accounts.signer.key() });
+        Ok(())
+    }
+
+    // ─── Unpause: only the admin resumes operations ──────────────────────
+    pub fn unpause(ctx: Context<Unpause>, flags: u8) -> Result<()> {
+        require!(flags != 0 && flags & !PAUSE_ALL == 0, InsuranceError::InvalidConfig);
+        let cfg = &mut ctx.accounts.config;
+        cfg.paused &= !flags;
+
+        emit!(PauseUpdated { paused: cfg.paused, by: ctx.accounts.admin.key() });
         Ok(())
     }
 
-    pub fn remove_cover_type(_ctx: Context<RemoveCoverType>) -> Result<()> {
-        // Just close the account
+    // ─── ProposeAdmin: step 1 of handover, new key must accept ───────────
+    pub fn propose_admin(ctx: Context<ProposeAdmin>, new_admin: Pubkey) -> Result<()> {
+        // Proposing Pubkey::default() cancels a pending handover
+        let cfg = &mut ctx.accounts.config;
+        cfg.pending_admin = new_admin;
+
+        emit!(AdminProposed { admin: cfg.admin, pending_admin: new_admin });
         Ok(())
     }
+
+    // ─── AcceptAdmin: step 2, proposed key signs to take over ────────────
+    pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+        let cfg = &mut ctx.accounts.config;
+        let roles = (cfg.underwriter, cfg.pauser);
+        let previous = accept_pending_admin(cfg);
+
+        emit!(AdminAccepted { previous, admin: cfg.admin });
+        if roles != (cfg.underwriter, cfg.pauser) {
+            emit!(RolesUpdated { underwriter: cfg.underwriter, pauser: cfg.pauser });
+        }
+        Ok(())
+    }
+}
+
+// ─── Helpers ────────────────────────────────────────────────────────────────
+
+/// Make the pending admin the admin, moving over every role the old admin
+/// still held so the old key keeps no power. Returns the old admin.
+fn accept_pending_admin(cfg: &mut Config) -> Pubkey {
+    let previous = cfg.admin;
+    let next     = cfg.pending_admin;
+    if cfg.underwriter == previous {
+        cfg.underwriter = next;
+    }
+    if cfg.pauser == previous {
+        cfg.pauser = next;
+    }
+    cfg.admin         = next;
+    cfg.pending_admin = Pubkey::default();
+    previous
+}
+
+fn require_unpaused(cfg: &Config, flag: u8) -> Result<()> {
+    require!(cfg.paused & flag == 0, InsuranceError::Paused);
+    Ok(())
+}
+
+fn validate_pricing(p: &CoverPricing) -> Result<()> {
+    require!(p.annual_rate_bps > 0, InsuranceError::InvalidPricing);
+    require!(
+        p.min_duration > 0 && p.min_duration <= p.max_duration && p.max_duration <= MAX_COVER_DURATION,
+        InsuranceError::InvalidPricing
+    );
+    require!(
+        p.min_amount > 0 && p.min_amount <= p.max_amount,
+        InsuranceError::InvalidPricing
+    );
+    Ok(())
+}
+
+/// Premium for `amount` of cover over `duration` seconds, rounded up so the
+/// pool never undercharges: amount * rate_bps * duration / (10_000 * 1 year).
+fn quote_premium(p: &CoverPricing, amount: u64, duration: i64) -> Result<u64> {
+    require!(
+        amount >= p.min_amount && amount <= p.max_amount,
+        InsuranceError::CoverAmountOutOfRange
+    );
+    require!(
+        duration >= p.min_duration && duration <= p.max_duration,
+        InsuranceError::DurationOutOfRange
+    );
+    let num = (amount as u128)
+        .checked_mul(p.annual_rate_bps as u128)
+        .and_then(|v| v.checked_mul(duration as u128))
+        .ok_or(InsuranceError::MathOverflow)?;
+    let den = BPS_DENOM * SECONDS_PER_YEAR;
+    let premium = num.div_ceil(den);
+    u64::try_from(premium).map_err(|_| error!(InsuranceError::MathOverflow))
+}
+
+/// Maximum active cover `total_staked` can back at `leverage_bps` (10_000 = 1x)
+fn cover_capacity(total_staked: u64, leverage_bps: u32) -> u128 {
+    (total_staked as u128) * (leverage_bps as u128) / BPS_DENOM
+}
+
+/// USDC value of `shares` at `index`, rounded down
+fn shares_value(shares: u64, index: u128) -> Result<u64> {
+    let value = (shares as u128)
+        .checked_mul(index)
+        .ok_or(InsuranceError::MathOverflow)?
+        / INDEX_PRECISION;
+    u64::try_from(value).map_err(|_| error!(InsuranceError::MathOverflow))
+}
+
+/// Shares worth `amount` at `index`; rounded down when minting and up when
+/// burning, so rounding always favours the pool
+fn shares_for(amount: u64, index: u128, round_up: bool) -> Result<u64> {
+    require!(index > 0, InsuranceError::MathOverflow);
+    let num = (amount as u128) * INDEX_PRECISION;
+    let shares = if round_up { num.div_ceil(index) } else { num / index };
+    u64::try_from(shares).map_err(|_| error!(InsuranceError::MathOverflow))
+}
+
+/// Lower the share price so all stakers, including those in cooldown,
+/// absorb `loss` in proportion to their shares. A loss that leaves the
+/// shares worthless voids them all and starts a new epoch at par.
+fn apply_loss(cfg: &mut Config, pool: &mut PoolState, loss: u64, now: i64) -> Result<()> {
+    let shares = cfg.total_shares.checked_add(cfg.pending_shares).unwrap();
+    let value = shares_value(shares, cfg.stake_index)?;
+    if loss == 0 || value == 0 {
+        return Ok(());
+    }
+    let index = cfg
+        .stake_index
+        .checked_mul(value.saturating_sub(loss) as u128)
+        .ok_or(InsuranceError::MathOverflow)?
+        / value as u128;
+    if index == 0 {
+        // Snapshot what the voided shares earned so their holders keep it
+        accrue_rewards(pool, cfg.total_shares, now)?;
+        pool.acc_at_wipe = pool.acc_reward_per_share;
+        cfg.stake_epoch    = cfg.stake_epoch.checked_add(1).unwrap();
+        cfg.total_shares   = 0;
+        cfg.pending_shares = 0;
+        cfg.stake_index    = INDEX_PRECISION;
+        cfg.total_staked   = 0;
+        return Ok(());
+    }
+    cfg.stake_index  = index;
+    cfg.total_staked = shares_value(cfg.total_shares, index)?;
+    Ok(())
+}
+
+/// Clear a stake voided by a full write-down since it was last touched,
+/// banking the rewards it had earned by then. A stake that slept through
+/// more than one write-down forfeits those rewards, as only the latest
+/// snapshot is kept.
+fn sync_stake_epoch(rec: &mut StakeRec, cfg: &Config, pool: &PoolState) -> Result<()> {
+    if rec.epoch == cfg.stake_epoch {
+        return Ok(());
+    }
+    if rec.epoch.checked_add(1) == Some(cfg.stake_epoch) {
+        let earned = (rec.shares as u128)
+            .checked_mul(pool.acc_at_wipe)
+            .ok_or(InsuranceError::MathOverflow)?
+            / REWARD_PRECISION;
+        let earned = u64::try_from(earned.saturating_sub(rec.reward_debt))
+            .map_err(|_| error!(InsuranceError::MathOverflow))?;
+        rec.pending_rewards = rec.pending_rewards.checked_add(earned).unwrap();
+    }
+    rec.shares         = 0;
+    rec.pending_shares = 0;
+    rec.reward_debt    = 0;
+    rec.checkpoint_len = 0;
+    rec.epoch          = cfg.stake_epoch;
+    Ok(())
+}
+
+/// USDC value of `rec`'s active shares; nothing if a write-down voided them
+fn staked_value(rec: &StakeRec, cfg: &Config) -> Result<u64> {
+    if rec.epoch != cfg.stake_epoch {
+        return Ok(0);
+    }
+    shares_value(rec.shares, cfg.stake_index)
+}
+
+/// Last bucket boundary at or before `cover_end`; the cover's premium
+/// stream stops there, so accrual never outlives the cover
+fn stream_end(cover_end: i64) -> i64 {
+    cover_end - cover_end.rem_euclid(REWARD_BUCKET)
+}
+
+/// Index into `PoolState.rate_cuts` for the boundary at `ts`
+fn rate_cut_slot(ts: i64) -> usize {
+    ts.div_euclid(REWARD_BUCKET).rem_euclid(REWARD_BUCKETS as i64) as usize
+}
+
+/// Stream premium accrued since the last update into `acc_reward_per_share`,
+/// dropping the rate at each bucket boundary where streams end
+fn accrue_rewards(pool: &mut PoolState, total_shares: u64, now: i64) -> Result<()> {
+    while pool.last_update_ts < now {
+        if pool.reward_rate == 0 {
+            pool.last_update_ts = now;
+            break;
+        }
+        let boundary = stream_end(pool.last_update_ts) + REWARD_BUCKET;
+        let until = boundary.min(now);
+        let elapsed = (until - pool.last_update_ts) as u128;
+        pool.last_update_ts = until;
+        // With nobody staked the premium stays undistributed for later stakers
+        if total_shares > 0 {
+            let streamed = pool
+                .reward_rate
+                .checked_mul(elapsed)
+                .ok_or(InsuranceError::MathOverflow)?
+                / REWARD_PRECISION;
+            let streamed = streamed.min(pool.undistributed as u128) as u64;
+            distribute_rewards(pool, streamed, total_shares)?;
+        }
+        if until == boundary {
+            let slot = rate_cut_slot(boundary);
+            pool.reward_rate = pool.reward_rate.checked_sub(pool.rate_cuts[slot]).unwrap();
+            pool.rate_cuts[slot] = 0;
+            // Once every stream has ended, only rounding dust is left
+            if pool.reward_rate == 0 {
+                distribute_rewards(pool, pool.undistributed, total_shares)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn distribute_rewards(pool: &mut PoolState, amount: u64, total_shares: u64) -> Result<()> {
+    if amount == 0 || total_shares == 0 {
+        return Ok(());
+    }
+    pool.undistributed = pool.undistributed.checked_sub(amount).unwrap();
+    let per_share = (amount as u128) * REWARD_PRECISION / (total_shares as u128);
+    pool.acc_reward_per_share = pool
+        .acc_reward_per_share
+        .checked_add(per_share)
+        .ok_or(InsuranceError::MathOverflow)?;
+    Ok(())
+}
+
+/// Schedule `premium` to stream until `stream_end(cover_end)` and return the
+/// stream's rate. A cover too short to span a bucket boundary pays its
+/// premium out at once.
+fn start_premium_stream(pool: &mut PoolState, premium: u64, cover_end: i64, total_shares: u64, now: i64) -> Result<u128> {
+    accrue_rewards(pool, total_shares, now)?;
+    pool.undistributed = pool.undistributed.checked_add(premium).unwrap();
+    let end = stream_end(cover_end);
+    if end <= now {
+        distribute_rewards(pool, premium, total_shares)?;
+        return Ok(0);
+    }
+    let rate = (premium as u128) * REWARD_PRECISION / ((end - now) as u128);
+    pool.reward_rate = pool.reward_rate.checked_add(rate).ok_or(InsuranceError::MathOverflow)?;
+    let slot = rate_cut_slot(end);
+    pool.rate_cuts[slot] = pool.rate_cuts[slot].checked_add(rate).ok_or(InsuranceError::MathOverflow)?;
+    Ok(rate)
+}
+
+/// Remove a cover's premium stream; premium not yet streamed (cover ended
+/// early) is paid out to current stakers at once.
+fn stop_premium_stream(pool: &mut PoolState, cover: &Cover, total_shares: u64, now: i64) -> Result<()> {
+    accrue_rewards(pool, total_shares, now)?;
+    // Past its end the stream was already cut by accrue_rewards
+    let end = stream_end(cover.start_ts + cover.duration);
+    if now < end {
+        pool.reward_rate = pool.reward_rate.checked_sub(cover.reward_rate).unwrap();
+        let slot = rate_cut_slot(end);
+        pool.rate_cuts[slot] = pool.rate_cuts[slot].checked_sub(cover.reward_rate).unwrap();
+        let unstreamed = cover.reward_rate * ((end - now) as u128) / REWARD_PRECISION;
+        let unstreamed = unstreamed.min(pool.undistributed as u128) as u64;
+        distribute_rewards(pool, unstreamed, total_shares)?;
+    }
+    Ok(())
+}
+
+fn reward_debt(shares: u64, pool: &PoolState) -> Result<u128> {
+    (shares as u128)
+        .checked_mul(pool.acc_reward_per_share)
+        .map(|v| v / REWARD_PRECISION)
+        .ok_or(error!(InsuranceError::MathOverflow))
+}
+
+/// Bank rewards earned at the current accumulator before `rec.shares` changes
+fn settle_rewards(rec: &mut StakeRec, pool: &PoolState) -> Result<()> {
+    let earned = reward_debt(rec.shares, pool)?.saturating_sub(rec.reward_debt);
+    let earned = u64::try_from(earned).map_err(|_| error!(InsuranceError::MathOverflow))?;
+    rec.pending_rewards = rec.pending_rewards.checked_add(earned).unwrap();
+    Ok(())
+}
+
+/// Record `amount` as the balance from `slot` on, evicting the oldest
+/// checkpoint once the history is full
+fn checkpoint_stake(rec: &mut StakeRec, amount: u64, slot: u64) {
+    let len = rec.checkpoint_len as usize;
+    if len > 0 && rec.checkpoints[len - 1].slot == slot {
+        rec.checkpoints[len - 1].amount = amount;
+        return;
+    }
+    let entry = StakeCheckpoint { slot, amount };
+    if len == STAKE_CHECKPOINTS {
+        rec.checkpoints.copy_within(1.., 0);
+        rec.checkpoints[len - 1] = entry;
+    } else {
+        rec.checkpoints[len] = entry;
+        rec.checkpoint_len += 1;
+    }
+}
+
+/// Balance held strictly before `slot`. Returns 0 when the history does not
+/// reach back that far, so evicted checkpoints can never inflate a vote.
+fn stake_at(rec: &StakeRec, slot: u64) -> u64 {
+    rec.checkpoints[..rec.checkpoint_len as usize]
+        .iter()
+        .rev()
+        .find(|c| c.slot < slot)
+        .map_or(0, |c| c.amount)
 }
 
 // ─── On‑chain State ─────────────────────────────────────────────────────────
 
 #[account]
 pub struct Config {
-    pub admin:     Pubkey,
-    pub usdc_mint: Pubkey,
-    pub bump:      u8,
+    pub admin:       Pubkey, // Full control: config, roles, cover types, claims
+    pub underwriter: Pubkey, // May re-price existing cover types
+    pub pauser:      Pubkey, // Emergency role, may only halt operations
+    pub usdc_mint:   Pubkey,
+    pub bump:        u8,
+    pub unstake_cooldown:   i64, // Seconds between request_unstake and withdraw_stake
+    pub total_staked:       u64, // Value of total_shares at stake_index (excludes pending unstakes)
+    pub total_active_cover: u64, // Sum of Cover.amount over active covers
+    pub leverage_bps:       u32, // Cover sold per unit of stake, in bps (10_000 = 1x, the maximum)
+    pub voting_period:      i64, // Seconds a claim stays open for votes
+    pub quorum_bps:         u16, // Share of total stake that must vote on a claim
+    pub paused:             u8,  // PAUSE_* bits; set by pauser or admin, cleared by admin only
+    pub pending_admin:      Pubkey, // Proposed admin, takes over once it signs accept_admin
+    pub total_shares:       u64,  // Sum of StakeRec.shares
+    pub stake_index:        u128, // USDC per share, scaled by INDEX_PRECISION; drops on payouts
+    pub pending_shares:     u64,  // Sum of StakeRec.pending_shares
+    pub stake_epoch:        u32,  // Bumped when a payout voids every share
 }
 
 #[account]
 pub struct StakeRec {
     pub staker: Pubkey,
-    pub amount: u64,
+    pub shares: u64, // Pool shares; worth shares * stake_index / INDEX_PRECISION USDC
     pub bump:   u8,
+    pub pending_shares:   u64, // In cooldown: no longer stake, but still written down
+    pub unstake_ready_ts: i64, // When pending_shares can be withdrawn
+    pub open_votes:       u32, // Votes cast on claims that are not settled yet
+    pub reward_debt:      u128, // shares * acc_reward_per_share already accounted for
+    pub pending_rewards:  u64,  // Earned but not yet claimed
+    pub checkpoints:      [StakeCheckpoint; STAKE_CHECKPOINTS], // Oldest first
+    pub checkpoint_len:   u8,
+    pub epoch:            u32, // Config.stake_epoch the shares belong to
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct StakeCheckpoint {
+    pub slot:   u64, // First slot at which `amount` was the staked balance
+    pub amount: u64,
+}
+
+/// Premium distribution state (MasterChef-style accumulator)
+#[account]
+pub struct PoolState {
+    pub acc_reward_per_share: u128, // Premium per stake share, scaled by REWARD_PRECISION
+    pub reward_rate:          u128, // Premium streamed per second, scaled by REWARD_PRECISION
+    pub undistributed:        u64,  // Premium received but not streamed yet
+    pub last_update_ts:       i64,
+    pub bump:                 u8,
+    pub rate_cuts:            [u128; REWARD_BUCKETS], // reward_rate that ends at each bucket boundary, by rate_cut_slot
+    pub acc_at_wipe:          u128, // acc_reward_per_share when shares were last voided
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum ClaimStatus {
+    Open,
+    Approved,
+    Denied,
 }
 
 #[account]
 pub struct Claim {
     pub claimant: Pubkey,
+    pub cover_id: u64,
+    pub claim_index:       u32,      // Position among the cover's claims
+    pub requested_amount:  u64,      // Loss claimed by the claimant
+    pub evidence_hash:     [u8; 32], // Hash of the off-chain evidence bundle
+    pub approved_weighted: u128,     // Sum over yes votes of weight * approved amount
+    pub payout:            u64,      // Amount paid on resolve
     pub yes:      u64,
     pub no:       u64,
     pub bump:     u8,
+    pub status:   ClaimStatus,
+    pub created_ts:      i64,
+    pub created_slot:    u64, // Vote weight is the stake held before this slot
+    pub voting_deadline: i64, // Votes accepted up to and including this time
+    pub quorum:          u64, // Minimum yes + no stake for the claim to be approved
 }
 
 #[account]
@@ -196,6 +1026,9 @@ pub struct Cover {
     pub duration: i64,       // Coverage duration (seconds)
     pub active: bool,
     pub bump: u8,
+    pub reward_rate: u128,   // Share of PoolState.reward_rate streamed by this cover
+    pub claims_filed: u32,   // Number of claims filed, seeds the next Claim PDA
+    pub open_claims: u32,    // Claims filed but not resolved; blocks expire_cover
 }
 
 /// Tracks all cover_ids for a user for easy querying
@@ -212,12 +1045,27 @@ pub struct CoverType {
     pub cover_id: u64,      // Unique cover type ID
     pub name: [u8; 32],     // Name of the cover (fixed size, UTF-8, null-padded)
     pub bump: u8,
+    pub pricing: CoverPricing,
+    pub active_cover: u64,  // Sum of Cover.amount over active covers of this type
+}
+
+/// Pricing model for a cover type; premiums are charged pro rata on the annual rate
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct CoverPricing {
+    pub annual_rate_bps: u16, // Yearly premium as bps of the covered amount
+    pub min_duration: i64,    // Seconds
+    pub max_duration: i64,    // Seconds
+    pub min_amount: u64,      // USDC base units
+    pub max_amount: u64,      // USDC base units
 }
 
 // ─── Add/Remove CoverType Admin Instructions ─────────────────────────
 #[derive(Accounts)]
 #[instruction(cover_id: u64)]
 pub struct AddCoverType<'info> {
+    #[account(seeds = [b"config"], bump = config.bump, has_one = admin)]
+    pub config: Account<'info, Config>,
+
     #[account(mut)]
     pub admin: Signer<'info>,
 
@@ -226,7 +1074,7 @@ pub struct AddCoverType<'info> {
         seeds = [b"cover_type"[..].as_ref(), &cover_id.to_le_bytes()],
         bump,
         payer = admin,
-        space = 8 + 32 + 32 + 1
+        space = 8 + 8 + 32 + 1 + (2 + 8 + 8 + 8 + 8) + 8
     )]
     pub cover_type: Account<'info, CoverType>,
 
@@ -237,6 +1085,9 @@ pub struct AddCoverType<'info> {
 #[derive(Accounts)]
 #[instruction(cover_id: u64)]
 pub struct RemoveCoverType<'info> {
+    #[account(seeds = [b"config"], bump = config.bump, has_one = admin)]
+    pub config: Account<'info, Config>,
+
     #[account(mut)]
     pub admin: Signer<'info>,
 
@@ -252,6 +1103,73 @@ pub struct RemoveCoverType<'info> {
     pub rent: Sysvar<'info, Rent>,
 }
 
+#[derive(Accounts)]
+#[instruction(cover_id: u64)]
+pub struct UpdateCoverType<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = signer.key() == config.admin || signer.key() == config.underwriter
+            @ InsuranceError::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    pub signer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"cover_type", &cover_id.to_le_bytes()],
+        bump = cover_type.bump,
+    )]
+    pub cover_type: Account<'info, CoverType>,
+}
+
+#[derive(Accounts)]
+pub struct SetRoles<'info> {
+    #[account(mut, seeds = [b"config"], bump = config.bump, has_one = admin)]
+    pub config: Account<'info, Config>,
+
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Pause<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = signer.key() == config.admin || signer.key() == config.pauser
+            @ InsuranceError::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    pub signer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Unpause<'info> {
+    #[account(mut, seeds = [b"config"], bump = config.bump, has_one = admin)]
+    pub config: Account<'info, Config>,
+
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeAdmin<'info> {
+    #[account(mut, seeds = [b"config"], bump = config.bump, has_one = admin)]
+    pub config: Account<'info, Config>,
+
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+    #[account(mut, seeds = [b"config"], bump = config.bump, has_one = pending_admin)]
+    pub config: Account<'info, Config>,
+
+    pub pending_admin: Signer<'info>,
+}
+
 // ─── Instruction Contexts ───────────────────────────────────────────────────
 
 #[derive(Accounts)]
@@ -262,9 +1180,17 @@ pub struct Initialize<'info> {
         seeds = [b"config"],
         bump,
         payer = payer,
-        space = 8 + 32 + 32 + 1
+        space = 8 + 32 + 32 + 32 + 32 + 1 + 8 + 8 + 8 + 4 + 8 + 2 + 1 + 32 + 8 + 16 + 8 + 4
     )]
     pub config:     Account<'info, Config>,
+    #[account(
+        init,
+        seeds = [b"pool"],
+        bump,
+        payer = payer,
+        space = 8 + 16 + 16 + 8 + 8 + 1 + (16 * REWARD_BUCKETS) + 16
+    )]
+    pub pool:       Box<Account<'info, PoolState>>,
     pub usdc_mint:  Account<'info, Mint>,
     #[account(mut)]
     pub payer:      Signer<'info>,
@@ -274,9 +1200,12 @@ pub struct Initialize<'info> {
 
 #[derive(Accounts)]
 pub struct Stake<'info> {
-    #[account(mut)]
+    #[account(mut, seeds = [b"config"], bump = config.bump)]
     pub config:    Account<'info, Config>,
 
+    #[account(mut, seeds = [b"pool"], bump = pool.bump)]
+    pub pool:      Box<Account<'info, PoolState>>,
+
     #[account(mut)]
     pub staker:    Signer<'info>,
 
@@ -285,13 +1214,14 @@ pub struct Stake<'info> {
         seeds = [b"stake", staker.key().as_ref()],
         bump,
         payer = staker,
-        space = 8 + 32 + 8 + 1
+        space = 8 + 32 + 8 + 1 + 8 + 8 + 4 + 16 + 8 + (16 * STAKE_CHECKPOINTS) + 1 + 4
     )]
     pub stake_rec: Account<'info, StakeRec>,
 
     #[account(mut)]
     pub staker_ata: Account<'info, TokenAccount>,
 
+    #[account(address = config.usdc_mint)]
     pub usdc_mint: Account<'info, Mint>,
 
     /// Pool vault ATA for USDC (ATA seeds = [usdc_mint, pool_authority])
@@ -313,10 +1243,99 @@ pub struct Stake<'info> {
     pub rent:                     Sysvar<'info, Rent>,
 }
 
+#[derive(Accounts)]
+pub struct RequestUnstake<'info> {
+    #[account(mut, seeds = [b"config"], bump = config.bump)]
+    pub config:    Account<'info, Config>,
+
+    #[account(mut, seeds = [b"pool"], bump = pool.bump)]
+    pub pool:      Box<Account<'info, PoolState>>,
+
+    pub staker:    Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"stake", staker.key().as_ref()],
+        bump = stake_rec.bump,
+        has_one = staker
+    )]
+    pub stake_rec: Account<'info, StakeRec>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawStake<'info> {
+    #[account(mut, seeds = [b"config"], bump = config.bump)]
+    pub config:    Account<'info, Config>,
+
+    #[account(seeds = [b"pool"], bump = pool.bump)]
+    pub pool:      Box<Account<'info, PoolState>>,
+
+    pub staker:    Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"stake", staker.key().as_ref()],
+        bump = stake_rec.bump,
+        has_one = staker
+    )]
+    pub stake_rec: Account<'info, StakeRec>,
+
+    #[account(mut, token::mint = config.usdc_mint)]
+    pub staker_ata: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = config.usdc_mint,
+        token::authority = pool_authority
+    )]
+    pub pool_vault: Account<'info, TokenAccount>,
+
+    /// CHECK: This is a PDA authority, not a real account. No data is read or written.
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub pool_authority: AccountInfo<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimRewards<'info> {
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config:    Account<'info, Config>,
+
+    #[account(mut, seeds = [b"pool"], bump = pool.bump)]
+    pub pool:      Box<Account<'info, PoolState>>,
+
+    pub staker:    Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"stake", staker.key().as_ref()],
+        bump = stake_rec.bump,
+        has_one = staker
+    )]
+    pub stake_rec: Account<'info, StakeRec>,
+
+    #[account(mut, token::mint = config.usdc_mint)]
+    pub staker_ata: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = config.usdc_mint,
+        token::authority = pool_authority
+    )]
+    pub pool_vault: Account<'info, TokenAccount>,
+
+    /// CHECK: This is a PDA authority, not a real account. No data is read or written.
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub pool_authority: AccountInfo<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
 #[derive(Accounts)]
 #[instruction(cover_id: u64)]
 pub struct CreateClaim<'info> {
-    #[account(mut)]
+    #[account(seeds = [b"config"], bump = config.bump)]
     pub config:    Account<'info, Config>,
 
     #[account(mut)]
@@ -332,10 +1351,15 @@ pub struct CreateClaim<'info> {
 
     #[account(
         init,
-        seeds = [b"claim", claimant.key().as_ref(), &cover_id.to_le_bytes()],
+        seeds = [
+            b"claim",
+            claimant.key().as_ref(),
+            &cover_id.to_le_bytes(),
+            &cover.claims_filed.to_le_bytes(),
+        ],
         bump,
         payer = claimant,
-        space = 8 + 32 + 8 + 8 + 1
+        space = 8 + 32 + 8 + 4 + 8 + 32 + 16 + 8 + 8 + 8 + 1 + 1 + 8 + 8 + 8 + 8
     )]
     pub claim:     Account<'info, Claim>,
 
@@ -345,17 +1369,37 @@ pub struct CreateClaim<'info> {
 
 #[derive(Accounts)]
 pub struct Vote<'info> {
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config:    Account<'info, Config>,
+
     #[account(mut)]
+    pub staker:    Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"claim",
+            claim.claimant.as_ref(),
+            &claim.cover_id.to_le_bytes(),
+            &claim.claim_index.to_le_bytes(),
+        ],
+        bump = claim.bump
+    )]
     pub claim:     Account<'info, Claim>,
 
-    #[account(mut)]
+    #[account(
+        mut,
+        seeds = [b"stake", staker.key().as_ref()],
+        bump = stake_rec.bump,
+        has_one = staker
+    )]
     pub stake_rec: Account<'info, StakeRec>,
 
     #[account(
         init_if_needed,
-        seeds = [b"vote", claim.key().as_ref(), stake_rec.staker.as_ref()],
+        seeds = [b"vote", claim.key().as_ref(), staker.key().as_ref()],
         bump,
-        payer = stake_rec,
+        payer = staker,
         space = 8 + 1 + 1
     )]
     pub vote_rec:  Account<'info, VoteRec>,
@@ -364,15 +1408,61 @@ pub struct Vote<'info> {
     pub rent:           Sysvar<'info, Rent>,
 }
 
+#[derive(Accounts)]
+pub struct ReleaseVote<'info> {
+    #[account(mut)]
+    pub staker:    Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"stake", staker.key().as_ref()],
+        bump = stake_rec.bump,
+        has_one = staker
+    )]
+    pub stake_rec: Account<'info, StakeRec>,
+
+    #[account(
+        seeds = [
+            b"claim",
+            claim.claimant.as_ref(),
+            &claim.cover_id.to_le_bytes(),
+            &claim.claim_index.to_le_bytes(),
+        ],
+        bump = claim.bump
+    )]
+    pub claim:     Account<'info, Claim>,
+
+    #[account(
+        mut,
+        close = staker,
+        seeds = [b"vote", claim.key().as_ref(), staker.key().as_ref()],
+        bump = vote_rec.bump
+    )]
+    pub vote_rec:  Account<'info, VoteRec>,
+}
+
 #[derive(Accounts)]
 #[instruction(cover_id: u64)]
 pub struct Resolve<'info> {
-    #[account(mut, has_one = admin)]
+    #[account(mut, seeds = [b"config"], bump = config.bump, has_one = admin)]
     pub config: Account<'info, Config>,
 
+    #[account(mut, seeds = [b"pool"], bump = pool.bump)]
+    pub pool: Box<Account<'info, PoolState>>,
+
     pub admin: Signer<'info>,
 
-    #[account(mut)]
+    #[account(
+        mut,
+        seeds = [
+            b"claim",
+            claim.claimant.as_ref(),
+            &claim.cover_id.to_le_bytes(),
+            &claim.claim_index.to_le_bytes(),
+        ],
+        bump = claim.bump,
+        constraint = claim.cover_id == cover_id
+    )]
     pub claim:     Account<'info, Claim>,
 
     #[account(mut,
@@ -381,7 +1471,18 @@ pub struct Resolve<'info> {
     )]
     pub cover:     Account<'info, Cover>,
 
-    #[account(mut)]
+    #[account(
+        mut,
+        seeds = [b"cover_type", &cover_id.to_le_bytes()],
+        bump = cover_type.bump,
+    )]
+    pub cover_type: Account<'info, CoverType>,
+
+    #[account(
+        mut,
+        token::mint = config.usdc_mint,
+        token::authority = pool_authority
+    )]
     pub pool_vault: Account<'info, TokenAccount>,
 
     /// Re-use the same PDA authority
@@ -389,18 +1490,49 @@ pub struct Resolve<'info> {
     #[account(seeds = [b"config"], bump = config.bump)]
     pub pool_authority: AccountInfo<'info>,
 
-    #[account(mut)]
+    #[account(
+        mut,
+        token::mint = config.usdc_mint,
+        token::authority = claim.claimant
+    )]
     pub claimant_ata: Account<'info, TokenAccount>,
 
     pub token_program: Program<'info, Token>,
 }
 
+#[derive(Accounts)]
+#[instruction(cover_id: u64)]
+pub struct ExpireCover<'info> {
+    #[account(mut, seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    #[account(mut, seeds = [b"pool"], bump = pool.bump)]
+    pub pool: Box<Account<'info, PoolState>>,
+
+    #[account(
+        mut,
+        seeds = [b"cover", cover.claimant.as_ref(), &cover_id.to_le_bytes()],
+        bump = cover.bump,
+    )]
+    pub cover: Account<'info, Cover>,
+
+    #[account(
+        mut,
+        seeds = [b"cover_type", &cover_id.to_le_bytes()],
+        bump = cover_type.bump,
+    )]
+    pub cover_type: Account<'info, CoverType>,
+}
+
 #[derive(Accounts)]
 #[instruction(cover_id: u64)]
 pub struct BuyCover<'info> {
-    #[account(mut)]
+    #[account(mut, seeds = [b"config"], bump = config.bump)]
     pub config: Account<'info, Config>,
 
+    #[account(mut, seeds = [b"pool"], bump = pool.bump)]
+    pub pool: Box<Account<'info, PoolState>>,
+
     #[account(mut)]
     pub claimant: Signer<'info>,
 
@@ -416,7 +1548,7 @@ pub struct BuyCover<'info> {
         seeds = [b"cover", claimant.key().as_ref(), &cover_id.to_le_bytes()],
         bump,
         payer = claimant,
-        space = 8 + 32 + 8 + 8 + 8 + 8 + 1 + 1 + 8 // extra for cover_id
+        space = 8 + 32 + 8 + 32 + 8 + 8 + 8 + 8 + 1 + 1 + 16 + 4 + 4
     )]
     pub cover: Account<'info, Cover>,
 
@@ -432,31 +1564,548 @@ pub struct BuyCover<'info> {
     #[account(mut)]
     pub claimant_ata: Account<'info, TokenAccount>,
 
-    #[account(mut)]
+    #[account(
+        mut,
+        token::mint = config.usdc_mint,
+        token::authority = pool_authority
+    )]
     pub pool_vault: Account<'info, TokenAccount>,
 
+    /// CHECK: This is a PDA authority, not a real account. No data is read or written.
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub pool_authority: AccountInfo<'info>,
+
     pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
     pub rent: Sysvar<'info, Rent>,
 }
 
+// ─── Events ──────────────────────────────────────────────────────────────────
+
+#[event]
+pub struct ConfigInitialized {
+    pub admin: Pubkey,
+    pub usdc_mint: Pubkey,
+    pub unstake_cooldown: i64,
+    pub leverage_bps: u32,
+    pub voting_period: i64,
+    pub quorum_bps: u16,
+}
+
+#[event]
+pub struct StakeEvent {
+    pub staker: Pubkey,
+    pub amount: u64,
+    pub staker_total: u64,
+    pub total_staked: u64,
+}
+
+#[event]
+pub struct UnstakeRequested {
+    pub staker: Pubkey,
+    pub amount: u64,
+    pub pending_unstake: u64,
+    pub ready_ts: i64,
+    pub total_staked: u64,
+}
+
+#[event]
+pub struct StakeWithdrawn {
+    pub staker: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct RewardsClaimed {
+    pub staker: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct CoverPurchased {
+    pub claimant: Pubkey,
+    pub cover_id: u64,
+    pub amount: u64,
+    pub premium: u64,
+    pub start_ts: i64,
+    pub duration: i64,
+    pub total_active_cover: u64,
+}
+
+#[event]
+pub struct CoverLapsed {
+    pub claimant: Pubkey,
+    pub cover_id: u64,
+    pub amount: u64,
+    pub total_active_cover: u64,
+}
+
+#[event]
+pub struct ClaimCreated {
+    pub claim: Pubkey,
+    pub claimant: Pubkey,
+    pub cover_id: u64,
+    pub claim_index: u32,
+    pub requested_amount: u64,
+    pub evidence_hash: [u8; 32],
+    pub voting_deadline: i64,
+    pub quorum: u64,
+}
+
+#[event]
+pub struct VoteCast {
+    pub claim: Pubkey,
+    pub staker: Pubkey,
+    pub approve: bool,
+    pub weight: u64,
+    pub approved_amount: u64, // 0 for no votes
+    pub yes: u64,
+    pub no: u64,
+}
+
+#[event]
+pub struct VoteReleased {
+    pub claim: Pubkey,
+    pub staker: Pubkey,
+    pub open_votes: u32,
+}
+
+#[event]
+pub struct StakeWrittenDown {
+    pub loss: u64,
+    pub stake_index: u128,
+    pub total_staked: u64,
+}
+
+#[event]
+pub struct ClaimResolved {
+    pub claim: Pubkey,
+    pub claimant: Pubkey,
+    pub cover_id: u64,
+    pub approved: bool,
+    pub yes: u64,
+    pub no: u64,
+    pub payout: u64,
+    pub remaining_cover: u64,
+}
+
+#[event]
+pub struct CoverTypeAdded {
+    pub cover_id: u64,
+    pub name: [u8; 32],
+    pub pricing: CoverPricing,
+}
+
+#[event]
+pub struct CoverTypeUpdated {
+    pub cover_id: u64,
+    pub pricing: CoverPricing,
+    pub updated_by: Pubkey,
+}
+
+#[event]
+pub struct CoverTypeRemoved {
+    pub cover_id: u64,
+}
+
+#[event]
+pub struct RolesUpdated {
+    pub underwriter: Pubkey,
+    pub pauser: Pubkey,
+}
+
+#[event]
+pub struct PauseUpdated {
+    pub paused: u8,
+    pub by: Pubkey,
+}
+
+#[event]
+pub struct AdminProposed {
+    pub admin: Pubkey,
+    pub pending_admin: Pubkey,
+}
+
+#[event]
+pub struct AdminAccepted {
+    pub previous: Pubkey,
+    pub admin: Pubkey,
+}
+
 #[error_code]
 pub enum InsuranceError {
     #[msg("No stake found")]
     NoStake,
     #[msg("Already voted")]
     AlreadyVoted,
-    #[msg("Claim not approved")]
-    ClaimDenied,
     #[msg("No active cover")]
     NoActiveCover,
     #[msg("Cover not started yet")]
     CoverNotStarted,
     #[msg("Cover expired")]
     CoverExpired,
+    #[msg("Invalid config parameter")]
+    InvalidConfig,
+    #[msg("Amount exceeds staked balance")]
+    InsufficientStake,
+    #[msg("Stake is locked by a vote on an open claim")]
+    StakeLockedByVote,
+    #[msg("Not enough staked capital to back active cover")]
+    InsufficientCapacity,
+    #[msg("Unstake cooldown has not elapsed")]
+    CooldownActive,
+    #[msg("Nothing to withdraw")]
+    NothingToWithdraw,
+    #[msg("Claim already settled")]
+    ClaimClosed,
+    #[msg("Claim not settled yet")]
+    ClaimStillOpen,
+    #[msg("No vote recorded")]
+    NoVote,
+    #[msg("Invalid cover pricing")]
+    InvalidPricing,
+    #[msg("Cover amount outside allowed range")]
+    CoverAmountOutOfRange,
+    #[msg("Cover duration outside allowed range")]
+    DurationOutOfRange,
+    #[msg("Math overflow")]
+    MathOverflow,
+    #[msg("Cover has not expired yet")]
+    CoverNotExpired,
+    #[msg("Cover type still has active cover")]
+    CoverTypeInUse,
+    #[msg("Voting period has ended")]
+    VotingClosed,
+    #[msg("Voting period has not ended")]
+    VotingStillOpen,
+    #[msg("Signer lacks the required role")]
+    Unauthorized,
+    #[msg("Claim amount must be positive and within remaining cover")]
+    InvalidClaimAmount,
+    #[msg("Operation is paused")]
+    Paused,
+    #[msg("Cover has unresolved claims")]
+    ClaimsPending,
 }
 
 // Querying covers:
 // - To get all covers for a user: fetch UserCovers PDA [b"user_covers", user_pubkey]
 //   and then fetch each Cover PDA [b"cover", user_pubkey, &cover_id.to_le_bytes()] for each cover_id in cover_ids.
-// - To filter by cover size: fetch all covers for the user, then filter by amount in client code.
\ No newline at end of file
+// - To filter by cover size: fetch all covers for the user, then filter by amount in client code.
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(total_shares: u64, stake_index: u128) -> Config {
+        Config {
+            admin: Pubkey::default(),
+            underwriter: Pubkey::default(),
+            pauser: Pubkey::default(),
+            usdc_mint: Pubkey::default(),
+            bump: 0,
+            unstake_cooldown: 0,
+            total_staked: shares_value(total_shares, stake_index).unwrap(),
+            total_active_cover: 0,
+            leverage_bps: MAX_LEVERAGE_BPS,
+            voting_period: 0,
+            quorum_bps: 0,
+            paused: 0,
+            pending_admin: Pubkey::default(),
+            total_shares,
+            stake_index,
+            pending_shares: 0,
+            stake_epoch: 0,
+        }
+    }
+
+    #[test]
+    fn payout_is_shared_pro_rata_and_every_staker_can_exit() {
+        // Two stakers put 300 and 700 into the vault at par
+        let mut vault: u64 = 1_000;
+        let a = shares_for(300, INDEX_PRECISION, false).unwrap();
+        let b = shares_for(700, INDEX_PRECISION, false).unwrap();
+        let mut cfg = config(a + b, INDEX_PRECISION);
+
+        // An approved claim pays 250 out of the vault
+        vault -= 250;
+        apply_loss(&mut cfg, &mut pool(), 250, 0).unwrap();
+        assert_eq!(cfg.total_staked, 750);
+
+        let (va, vb) = (shares_value(a, cfg.stake_index).unwrap(), shares_value(b, cfg.stake_index).unwrap());
+        assert_eq!((va, vb), (225, 525));
+
+        // Both withdraw everything; the vault covers them and nothing is left owed
+        for (shares, value) in [(a, va), (b, vb)] {
+            let burned = shares_for(value, cfg.stake_index, true).unwrap();
+            assert!(burned <= shares);
+            cfg.total_shares -= burned;
+            vault = vault.checked_sub(value).unwrap();
+        }
+        assert_eq!(cfg.total_shares, 0);
+        assert_eq!(vault, 0);
+    }
+
+    #[test]
+    fn rounding_never_leaves_the_vault_short() {
+        let stakes = [1u64, 7, 333, 1_000_001, 42];
+        let mut vault: u64 = stakes.iter().sum();
+        let mut cfg = config(0, INDEX_PRECISION);
+        let mut shares = Vec::new();
+        for s in stakes {
+            let minted = shares_for(s, cfg.stake_index, false).unwrap();
+            cfg.total_shares += minted;
+            cfg.total_staked = shares_value(cfg.total_shares, cfg.stake_index).unwrap();
+            shares.push(minted);
+        }
+        for loss in [3u64, 99_999, 17] {
+            vault -= loss;
+            apply_loss(&mut cfg, &mut pool(), loss, 0).unwrap();
+        }
+        let owed: u64 = shares.iter().map(|s| shares_value(*s, cfg.stake_index).unwrap()).sum();
+        assert!(owed <= cfg.total_staked && cfg.total_staked <= vault);
+    }
+
+    #[test]
+    fn shares_in_cooldown_absorb_losses() {
+        // 500 active and 500 waiting out the cooldown
+        let mut cfg = config(500, INDEX_PRECISION);
+        cfg.pending_shares = 500;
+        apply_loss(&mut cfg, &mut pool(), 100, 0).unwrap();
+        assert_eq!(shares_value(cfg.pending_shares, cfg.stake_index).unwrap(), 450);
+        assert_eq!(cfg.total_staked, 450);
+    }
+
+    #[test]
+    fn full_write_down_voids_old_shares_and_lets_new_stake_in() {
+        let mut cfg = config(100, INDEX_PRECISION);
+        cfg.pending_shares = 50;
+        let mut p = pool();
+        p.acc_reward_per_share = 2 * REWARD_PRECISION;
+        let mut old = stake_rec(100);
+        old.reward_debt = reward_debt(100, &p).unwrap();
+        checkpoint_stake(&mut old, 100, 5);
+        p.acc_reward_per_share = 3 * REWARD_PRECISION;
+
+        // One payout takes every last unit of stake
+        apply_loss(&mut cfg, &mut p, 150, 0).unwrap();
+        assert_eq!((cfg.total_shares, cfg.pending_shares, cfg.total_staked), (0, 0, 0));
+        assert_eq!((cfg.stake_index, cfg.stake_epoch), (INDEX_PRECISION, 1));
+
+        // The old holder's shares are worth nothing and cannot vote
+        assert_eq!(staked_value(&old, &cfg).unwrap(), 0);
+
+        // A fresh stake mints at par
+        let mut fresh = stake_rec(0);
+        sync_stake_epoch(&mut fresh, &cfg, &p).unwrap();
+        let minted = shares_for(200, cfg.stake_index, false).unwrap();
+        fresh.shares = minted;
+        cfg.total_shares = minted;
+        assert_eq!(staked_value(&fresh, &cfg).unwrap(), 200);
+
+        // Touching the old record clears it but keeps the rewards it earned
+        sync_stake_epoch(&mut old, &cfg, &p).unwrap();
+        assert_eq!((old.shares, old.pending_shares, old.checkpoint_len), (0, 0, 0));
+        assert_eq!(old.pending_rewards, 100);
+        assert_eq!(stake_at(&old, 10), 0);
+    }
+    #[test]
+    fn accepting_admin_strips_every_role_from_the_old_key() {
+        let (old, new, other) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let mut cfg = config(0, INDEX_PRECISION);
+        cfg.admin = old;
+        cfg.underwriter = old;
+        cfg.pauser = old;
+        cfg.pending_admin = new;
+
+        assert_eq!(accept_pending_admin(&mut cfg), old);
+        assert_eq!((cfg.admin, cfg.underwriter, cfg.pauser), (new, new, new));
+        assert_eq!(cfg.pending_admin, Pubkey::default());
+
+        // Roles already handed to someone else stay where they are
+        cfg.pauser = other;
+        cfg.pending_admin = old;
+        accept_pending_admin(&mut cfg);
+        assert_eq!((cfg.admin, cfg.underwriter, cfg.pauser), (old, old, other));
+    }
+    const WEEK: i64 = REWARD_BUCKET;
+
+    fn pool() -> PoolState {
+        PoolState {
+            acc_reward_per_share: 0,
+            reward_rate: 0,
+            undistributed: 0,
+            last_update_ts: 0,
+            bump: 0,
+            rate_cuts: [0; REWARD_BUCKETS],
+            acc_at_wipe: 0,
+        }
+    }
+
+    fn stake_rec(shares: u64) -> StakeRec {
+        StakeRec {
+            staker: Pubkey::default(),
+            shares,
+            bump: 0,
+            pending_shares: 0,
+            unstake_ready_ts: 0,
+            open_votes: 0,
+            reward_debt: 0,
+            pending_rewards: 0,
+            checkpoints: [StakeCheckpoint::default(); STAKE_CHECKPOINTS],
+            checkpoint_len: 0,
+            epoch: 0,
+        }
+    }
+
+    fn cover(start_ts: i64, duration: i64, reward_rate: u128) -> Cover {
+        Cover {
+            claimant: Pubkey::default(),
+            cover_id: 0,
+            cover_name: [0; 32],
+            amount: 0,
+            premium_paid: 0,
+            start_ts,
+            duration,
+            active: true,
+            bump: 0,
+            claims_filed: 0,
+            reward_rate,
+            open_claims: 0,
+        }
+    }
+
+    fn distributed(pool: &PoolState, total_shares: u64) -> u128 {
+        pool.acc_reward_per_share * total_shares as u128 / REWARD_PRECISION
+    }
+
+    #[test]
+    fn distribute_rewards_splits_per_share() {
+        let mut p = pool();
+        p.undistributed = 1_000;
+        distribute_rewards(&mut p, 600, 300).unwrap();
+        assert_eq!(p.undistributed, 400);
+        assert_eq!(p.acc_reward_per_share, 2 * REWARD_PRECISION);
+
+        // Nothing moves while nobody is staked
+        distribute_rewards(&mut p, 400, 0).unwrap();
+        assert_eq!(p.undistributed, 400);
+    }
+
+    #[test]
+    fn settle_rewards_banks_only_what_was_earned_since_the_last_debt() {
+        let mut p = pool();
+        p.acc_reward_per_share = 3 * REWARD_PRECISION;
+        let mut rec = stake_rec(10);
+        rec.reward_debt = reward_debt(10, &p).unwrap();
+
+        p.acc_reward_per_share = 5 * REWARD_PRECISION;
+        settle_rewards(&mut rec, &p).unwrap();
+        assert_eq!(rec.pending_rewards, 20);
+    }
+
+    #[test]
+    fn accrue_rewards_streams_at_the_scheduled_rate() {
+        let mut p = pool();
+        let rate = start_premium_stream(&mut p, 2 * WEEK as u64, 2 * WEEK, 1, 0).unwrap();
+        assert_eq!(rate, REWARD_PRECISION);
+        accrue_rewards(&mut p, 1, 100).unwrap();
+        assert_eq!(distributed(&p, 1), 100);
+        assert_eq!(p.undistributed, 2 * WEEK as u64 - 100);
+    }
+
+    #[test]
+    fn accrue_rewards_stops_each_stream_at_its_cover_end() {
+        let mut p = pool();
+        // A one-week cover and a four-week cover, both bought at time 0
+        start_premium_stream(&mut p, WEEK as u64, WEEK, 1, 0).unwrap();
+        start_premium_stream(&mut p, 4 * WEEK as u64, 4 * WEEK, 1, 0).unwrap();
+
+        // Long after the short cover lapsed, with no expire_cover call, only the
+        // long cover keeps streaming
+        accrue_rewards(&mut p, 1, 3 * WEEK).unwrap();
+        assert_eq!(distributed(&p, 1), 4 * WEEK as u128);
+        assert_eq!(p.reward_rate, REWARD_PRECISION);
+
+        // Once both lapsed everything is streamed and nothing more accrues
+        accrue_rewards(&mut p, 1, 10 * WEEK).unwrap();
+        assert_eq!(distributed(&p, 1), 5 * WEEK as u128);
+        assert_eq!((p.reward_rate, p.undistributed), (0, 0));
+        assert!(p.rate_cuts.iter().all(|c| *c == 0));
+    }
+
+    #[test]
+    fn stream_ends_on_the_last_boundary_before_the_cover_ends() {
+        let mut p = pool();
+        // Ends half a week past a boundary: streams until that boundary
+        start_premium_stream(&mut p, 1_000, 2 * WEEK + WEEK / 2, 1, 0).unwrap();
+        accrue_rewards(&mut p, 1, 2 * WEEK).unwrap();
+        assert_eq!((p.reward_rate, distributed(&p, 1)), (0, 1_000));
+
+        // Too short to reach a boundary: paid out at once
+        let rate = start_premium_stream(&mut p, 500, 2 * WEEK + 10, 1, 2 * WEEK + 1).unwrap();
+        assert_eq!((rate, distributed(&p, 1)), (0, 1_500));
+    }
+
+    #[test]
+    fn stopping_a_stream_early_pays_the_rest_and_cancels_its_cut() {
+        let mut p = pool();
+        let rate = start_premium_stream(&mut p, 2 * WEEK as u64, 2 * WEEK, 1, 0).unwrap();
+        stop_premium_stream(&mut p, &cover(0, 2 * WEEK, rate), 1, WEEK / 2).unwrap();
+        assert_eq!(distributed(&p, 1), 2 * WEEK as u128);
+        assert_eq!(p.reward_rate, 0);
+        assert!(p.rate_cuts.iter().all(|c| *c == 0));
+
+        // Stopping after the end leaves the already-cut rate alone
+        let rate = start_premium_stream(&mut p, WEEK as u64, 2 * WEEK, 1, WEEK).unwrap();
+        accrue_rewards(&mut p, 1, 3 * WEEK).unwrap();
+        stop_premium_stream(&mut p, &cover(WEEK, WEEK, rate), 1, 3 * WEEK).unwrap();
+        assert_eq!(p.reward_rate, 0);
+    }
+    #[test]
+    fn stake_at_is_zero_before_the_first_checkpoint() {
+        let mut rec = stake_rec(0);
+        assert_eq!(stake_at(&rec, 100), 0);
+        checkpoint_stake(&mut rec, 500, 10);
+        assert_eq!(stake_at(&rec, 5), 0);
+        assert_eq!(stake_at(&rec, 10), 0);
+    }
+
+    #[test]
+    fn stake_at_an_exact_checkpoint_slot_sees_the_earlier_balance() {
+        let mut rec = stake_rec(0);
+        checkpoint_stake(&mut rec, 500, 10);
+        checkpoint_stake(&mut rec, 800, 20);
+        // Stake changed in the claim's own slot does not count for it
+        assert_eq!(stake_at(&rec, 20), 500);
+        assert_eq!(stake_at(&rec, 21), 800);
+        assert_eq!(stake_at(&rec, 15), 500);
+    }
+
+    #[test]
+    fn checkpoints_in_one_slot_keep_the_last_balance() {
+        let mut rec = stake_rec(0);
+        checkpoint_stake(&mut rec, 500, 10);
+        checkpoint_stake(&mut rec, 300, 10);
+        assert_eq!(rec.checkpoint_len, 1);
+        assert_eq!(stake_at(&rec, 11), 300);
+    }
+
+    #[test]
+    fn wrapped_history_never_resolves_an_old_slot_to_a_newer_balance() {
+        let mut rec = stake_rec(0);
+        // Slots 10, 20, ..: the first two are evicted once the ring wraps
+        for i in 1..=(STAKE_CHECKPOINTS as u64 + 2) {
+            checkpoint_stake(&mut rec, i * 100, i * 10);
+        }
+        assert_eq!(rec.checkpoint_len as usize, STAKE_CHECKPOINTS);
+        assert_eq!(rec.checkpoints[0].slot, 30);
+
+        // Slots covered only by evicted checkpoints read as zero, not as the
+        // balance recorded at a later slot
+        assert_eq!(stake_at(&rec, 15), 0);
+        assert_eq!(stake_at(&rec, 25), 0);
+        assert_eq!(stake_at(&rec, 30), 0);
+        assert_eq!(stake_at(&rec, 31), 300);
+        assert_eq!(stake_at(&rec, u64::MAX), (STAKE_CHECKPOINTS as u64 + 2) * 100);
+    }
+}
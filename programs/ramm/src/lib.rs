@@ -1,24 +1,66 @@
 use anchor_lang::{prelude::*, system_program};
 use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 
+mod math;
+mod pricing;
+
 declare_id!("EiiEQwTTXftYXysyZ2VnDomcUKGDMU2SMbSrEx3Zj2dJ");
 
 const ABOVE_SEED: &[u8] = b"above";
 const BELOW_SEED: &[u8] = b"below";
 const STATE_SEED: &[u8] = b"state";
 const DENOM: u64 = 1_000_000_000; // 1e9 token decimals
-const SECONDS_PER_DAY: u64 = 86_400;
+const SECONDS_PER_DAY: i64 = 86_400;
+const MIN_PARAM_TIMELOCK: i64 = SECONDS_PER_DAY; // users get a day to react to queued params
+
+// State.paused bits
+pub const PAUSE_TRADING: u8 = 1 << 0; // buy, sell
 
 #[account]
+#[derive(Default)]
 pub struct State {
     pub bump: u8,
     pub mint: Pubkey, // ← stores the ONLY valid ACR mint
     pub buf_bps: u16,
-    pub ratchet_bps_per_day: u16, // NEW: parametrize ratchet speed (e.g., 400 = 4.00%/day)
+    pub ratchet_bps_per_day: u16, // NEW: parametrize ratchet speed, compounded per second (400 = 4%/day)
     pub mcr: u128,
-    pub virt_above: u128,
-    pub virt_below: u128,
+    pub virt_above: u128, // spot buy price, lamports per 1 ACR (1e9 units)
+    pub virt_below: u128, // spot sell price, lamports per 1 ACR (1e9 units)
     pub last_ratchet: i64,
+    pub authority: Pubkey,          // may queue/cancel parameter changes
+    pub param_timelock: i64,        // seconds between queueing and applying params, >= 1 day
+    pub pending_params: RammParams, // queued change, valid when pending_eta != 0
+    pub pending_eta: i64,           // earliest apply time, 0 = nothing queued
+    pub mcr_capital_factor: u64,    // lamports of MCR per unit of actuary cover, scaled by DENOM
+    pub total_capital: u64,         // lamports held for ACR across both vaults, excluding rent
+    pub virt_liquidity: u64,        // virtual SOL reserve per side; sets price impact
+    pub daily_inject_limit: u64,    // max lamports injected per day by the authority
+    pub daily_extract_limit: u64,   // max lamports extracted per day by the authority
+    pub flow_window_start: i64,     // start of the current one-day liquidity window
+    pub injected_in_window: u64,
+    pub extracted_in_window: u64,
+    pub sol_release_cap: u64, // max lamports paid out by sells per rolling day, 0 = no cap
+    pub acr_burn_cap: u64,    // max ACR burned by sells per rolling day, 0 = no cap
+    pub breaker_updated_at: i64, // last time the breaker counters were decayed
+    pub sol_released_recent: u64, // decays linearly by sol_release_cap per day
+    pub acr_burned_recent: u64, // decays linearly by acr_burn_cap per day
+    pub guardian: Pubkey,     // may pause immediately; only the authority unpauses
+    pub paused: u8,           // PAUSE_* bits
+    pub pending_authority: Pubkey, // proposed authority, takes over once it signs accept_authority
+}
+
+/// Governable parameters; changes go through `update_params` + timelock
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct RammParams {
+    pub buf_bps: u16,
+    pub ratchet_bps_per_day: u16,
+    pub mcr: u128,
+    pub mcr_capital_factor: u64, // 0 keeps `mcr` static, otherwise MCR follows actuary cover
+    pub virt_liquidity: u64,
+    pub daily_inject_limit: u64,  // 0 disables inject_liquidity
+    pub daily_extract_limit: u64, // 0 disables extract_liquidity
+    pub sol_release_cap: u64,
+    pub acr_burn_cap: u64,
 }
 
 #[program]
@@ -31,7 +73,25 @@ pub mod ramm {
         buf_bps: u16,
         ratchet_bps_per_day: u16,
         mcr: u128,
+        param_timelock: i64,
+        virt_liquidity: u64,
     ) -> Result<()> {
+        validate_params(&RammParams {
+            buf_bps,
+            ratchet_bps_per_day,
+            mcr,
+            mcr_capital_factor: 0,
+            virt_liquidity,
+            daily_inject_limit: 0,
+            daily_extract_limit: 0,
+            sol_release_cap: 0,
+            acr_burn_cap: 0,
+        })?;
+        require!(
+            param_timelock >= MIN_PARAM_TIMELOCK,
+            ErrorCode::InvalidParams
+        );
+        let authority = ctx.accounts.authority.key();
         let st = &mut ctx.accounts.state;
         st.bump = ctx.bumps.state;
         st.mint = ctx.accounts.mint.key(); // NEW: persist mint
@@ -41,11 +101,320 @@ pub mod ramm {
         st.virt_above = 1_000_000_000; // 1 ACR virtual
         st.virt_below = 1_000_000_000;
         st.last_ratchet = Clock::get()?.unix_timestamp;
+        st.authority = authority;
+        st.param_timelock = param_timelock;
+        st.pending_params = RammParams::default();
+        st.pending_eta = 0;
+        st.mcr_capital_factor = 0;
+        st.total_capital = 0;
+        st.virt_liquidity = virt_liquidity;
+        st.daily_inject_limit = 0;
+        st.daily_extract_limit = 0;
+        st.flow_window_start = st.last_ratchet;
+        st.injected_in_window = 0;
+        st.extracted_in_window = 0;
+        st.sol_release_cap = 0;
+        st.acr_burn_cap = 0;
+        st.breaker_updated_at = st.last_ratchet;
+        st.sol_released_recent = 0;
+        st.acr_burned_recent = 0;
+        st.guardian = authority;
+        st.paused = 0;
+        st.pending_authority = Pubkey::default();
         Ok(())
     }
 
     // ----------------------------------------------------------------------
-    pub fn buy(ctx: Context<Trade>, lamports_in: u64) -> Result<()> {
+    // GOVERNANCE: authority queues params, anyone applies them after the timelock
+    pub fn update_params(ctx: Context<UpdateParams>, params: RammParams) -> Result<()> {
+        validate_params(&params)?;
+        let st = &mut ctx.accounts.state;
+        let eta = Clock::get()?
+            .unix_timestamp
+            .checked_add(st.param_timelock)
+            .ok_or(ErrorCode::MathOverflow)?;
+        st.pending_params = params;
+        st.pending_eta = eta;
+        emit!(ParamsQueued { params, eta });
+        Ok(())
+    }
+
+    pub fn apply_params(ctx: Context<ApplyParams>) -> Result<()> {
+        let st = &mut ctx.accounts.state;
+        require!(st.pending_eta != 0, ErrorCode::NoPendingParams);
+        require!(
+            Clock::get()?.unix_timestamp >= st.pending_eta,
+            ErrorCode::TimelockActive
+        );
+        let params = st.pending_params;
+        st.buf_bps = params.buf_bps;
+        st.ratchet_bps_per_day = params.ratchet_bps_per_day;
+        // A synced MCR tracks actuary cover; the static value only applies
+        // while syncing is disabled
+        if params.mcr_capital_factor == 0 {
+            st.mcr = params.mcr;
+        }
+        st.mcr_capital_factor = params.mcr_capital_factor;
+        st.virt_liquidity = params.virt_liquidity;
+        st.daily_inject_limit = params.daily_inject_limit;
+        st.daily_extract_limit = params.daily_extract_limit;
+        st.sol_release_cap = params.sol_release_cap;
+        st.acr_burn_cap = params.acr_burn_cap;
+        st.pending_params = RammParams::default();
+        st.pending_eta = 0;
+        emit!(ParamsApplied { params });
+        Ok(())
+    }
+
+    pub fn cancel_params(ctx: Context<UpdateParams>) -> Result<()> {
+        let st = &mut ctx.accounts.state;
+        require!(st.pending_eta != 0, ErrorCode::NoPendingParams);
+        st.pending_params = RammParams::default();
+        st.pending_eta = 0;
+        emit!(ParamsCancelled {});
+        Ok(())
+    }
+
+    // Re-open sells after the breaker tripped; counters restart from now
+    pub fn reset_circuit_breaker(ctx: Context<UpdateParams>) -> Result<()> {
+        let st = &mut ctx.accounts.state;
+        let now = Clock::get()?.unix_timestamp;
+        reset_breaker(st, now);
+        emit!(CircuitBreakerReset { timestamp: now });
+        Ok(())
+    }
+
+    // ----------------------------------------------------------------------
+    // HANDOVER: authority proposes, the new key must sign to accept.
+    // Proposing Pubkey::default() cancels a pending handover.
+    pub fn propose_authority(ctx: Context<UpdateParams>, new_authority: Pubkey) -> Result<()> {
+        let st = &mut ctx.accounts.state;
+        st.pending_authority = new_authority;
+        emit!(AuthorityProposed {
+            authority: st.authority,
+            pending_authority: new_authority,
+        });
+        Ok(())
+    }
+
+    pub fn accept_authority(ctx: Context<AcceptAuthority>) -> Result<()> {
+        let st = &mut ctx.accounts.state;
+        let guardian = st.guardian;
+        let previous = accept_pending_authority(st);
+        if st.guardian != guardian {
+            emit!(GuardianUpdated {
+                guardian: st.guardian,
+            });
+        }
+        emit!(AuthorityAccepted {
+            previous,
+            authority: st.authority,
+        });
+        Ok(())
+    }
+
+    // ----------------------------------------------------------------------
+    // PAUSE: guardian or authority halts trading, only the authority resumes
+    pub fn set_guardian(ctx: Context<UpdateParams>, guardian: Pubkey) -> Result<()> {
+        ctx.accounts.state.guardian = guardian;
+        emit!(GuardianUpdated { guardian });
+        Ok(())
+    }
+
+    pub fn pause(ctx: Context<Pause>, flags: u8) -> Result<()> {
+        require!(
+            flags != 0 && flags & !PAUSE_TRADING == 0,
+            ErrorCode::InvalidParams
+        );
+        let st = &mut ctx.accounts.state;
+        st.paused |= flags;
+        emit!(PauseUpdated {
+            paused: st.paused,
+            by: ctx.accounts.signer.key(),
+        });
+        Ok(())
+    }
+
+    pub fn unpause(ctx: Context<UpdateParams>, flags: u8) -> Result<()> {
+        require!(
+            flags != 0 && flags & !PAUSE_TRADING == 0,
+            ErrorCode::InvalidParams
+        );
+        let st = &mut ctx.accounts.state;
+        st.paused &= !flags;
+        emit!(PauseUpdated {
+            paused: st.paused,
+            by: ctx.accounts.authority.key(),
+        });
+        Ok(())
+    }
+
+    // ----------------------------------------------------------------------
+    // Recompute MCR from the cover the actuary program currently has in force
+    pub fn sync_mcr(ctx: Context<SyncMcr>) -> Result<()> {
+        let st = &mut ctx.accounts.state;
+        require!(st.mcr_capital_factor > 0, ErrorCode::McrSyncDisabled);
+        let active_cover = ctx.accounts.actuary_config.total_active_cover;
+        st.mcr = mcr_for_cover(active_cover, st.mcr_capital_factor)?;
+        emit!(McrSynced {
+            total_active_cover: active_cover,
+            mcr_capital_factor: st.mcr_capital_factor,
+            mcr: st.mcr,
+        });
+        Ok(())
+    }
+
+    // ----------------------------------------------------------------------
+    // BOOTSTRAP: authority funds an empty pool and mints the first ACR at
+    // `start_price`, so book value exists before the first buy
+    pub fn seed_liquidity(
+        ctx: Context<SeedLiquidity>,
+        lamports: u64,
+        start_price: u64,
+    ) -> Result<()> {
+        require!(ctx.accounts.mint.supply == 0, ErrorCode::AlreadySeeded);
+        require!(start_price > 0, ErrorCode::InvalidParams);
+        let amount = pricing::tokens_out(lamports, start_price)?;
+        require!(amount > 0, ErrorCode::TooLittleIn);
+
+        // 1) split the deposit across both vaults
+        let to_above = lamports / 2;
+        for (vault, share) in [
+            (&ctx.accounts.above_vault, to_above),
+            (&ctx.accounts.below_vault, lamports - to_above),
+        ] {
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.authority.to_account_info(),
+                        to: vault.to_account_info(),
+                    },
+                ),
+                share,
+            )?;
+        }
+
+        // 2) mint the initial supply to the authority
+        anchor_spl::token_interface::mint_to(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                anchor_spl::token_interface::MintTo {
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.authority_ata.to_account_info(),
+                    authority: ctx.accounts.state.to_account_info(),
+                },
+                &[&[
+                    STATE_SEED,
+                    ctx.accounts.mint.key().as_ref(),
+                    &[ctx.accounts.state.bump],
+                ]],
+            ),
+            amount,
+        )?;
+
+        // 3) start both virtual prices at the seeded price
+        seed_state(
+            &mut ctx.accounts.state,
+            lamports,
+            start_price,
+            Clock::get()?.unix_timestamp,
+        )?;
+
+        emit!(LiquiditySeeded {
+            authority: ctx.accounts.authority.key(),
+            lamports,
+            start_price,
+            amount,
+        });
+        Ok(())
+    }
+
+    // ----------------------------------------------------------------------
+    // LIQUIDITY: authority tops up or drains the vaults within daily limits
+    pub fn inject_liquidity(ctx: Context<ManageLiquidity>, lamports: u64) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        record_inject(&mut ctx.accounts.state, now, lamports)?;
+
+        // Below vault backs redemptions, so fresh capital goes there
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.authority.to_account_info(),
+                    to: ctx.accounts.below_vault.to_account_info(),
+                },
+            ),
+            lamports,
+        )?;
+
+        emit!(LiquidityInjected {
+            lamports,
+            total_capital: ctx.accounts.state.total_capital,
+            injected_in_window: ctx.accounts.state.injected_in_window,
+        });
+        Ok(())
+    }
+
+    pub fn extract_liquidity(ctx: Context<ManageLiquidity>, lamports: u64) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let st = &mut ctx.accounts.state;
+        refresh_mcr(st, ctx.accounts.actuary_config.as_deref())?;
+        record_extract(st, now, lamports)?;
+
+        let (from_above, from_below) = split_extract(
+            ctx.accounts.above_vault.lamports(),
+            ctx.accounts.below_vault.lamports(),
+            Rent::get()?.minimum_balance(0),
+            lamports,
+        )?;
+        let mint_key = ctx.accounts.mint.key();
+        for (vault, seed, bump, share) in [
+            (
+                &ctx.accounts.above_vault,
+                ABOVE_SEED,
+                ctx.bumps.above_vault,
+                from_above,
+            ),
+            (
+                &ctx.accounts.below_vault,
+                BELOW_SEED,
+                ctx.bumps.below_vault,
+                from_below,
+            ),
+        ] {
+            if share > 0 {
+                vault_transfer(
+                    vault,
+                    &ctx.accounts.authority,
+                    &ctx.accounts.system_program,
+                    &[seed, mint_key.as_ref(), &[bump]],
+                    share,
+                )?;
+            }
+        }
+
+        emit!(LiquidityExtracted {
+            lamports,
+            total_capital: ctx.accounts.state.total_capital,
+            extracted_in_window: ctx.accounts.state.extracted_in_window,
+        });
+        Ok(())
+    }
+
+    // ----------------------------------------------------------------------
+    pub fn buy(
+        ctx: Context<Trade>,
+        lamports_in: u64,
+        min_amount_out: u64,
+        deadline: i64,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.state.paused & PAUSE_TRADING == 0,
+            ErrorCode::Paused
+        );
+        let now = Clock::get()?.unix_timestamp;
+        require!(now <= deadline, ErrorCode::DeadlineExceeded);
         // NEW: hard‑check correct mint
         require!(
             ctx.accounts.mint.key() == ctx.accounts.state.mint,
@@ -68,12 +437,14 @@ pub mod ramm {
 
         // --- Clamp price to Book Value CEILING (BV * (1 + buffer))
         let (bv, floor, ceil) = book_value_and_bounds(&ctx)?;
+        // Bring virtual prices up to date before pricing the trade
+        ratchet_state(&mut ctx.accounts.state, floor, ceil, now);
         // Keep your virtual-based price as a signal, but clamp to BV bounds.
-        let mut price = buy_price_virtual(&ctx.accounts.state)?;
-        if price < ceil {
-            price = ceil;
-        }
-        require!(lamports_in >= price, ErrorCode::TooLittleIn);
+        let (start_price, amount_out, next_above) =
+            quote_buy_curve(&ctx.accounts.state, ceil, lamports_in)?;
+        require!(lamports_in >= start_price, ErrorCode::TooLittleIn);
+        require!(amount_out > 0, ErrorCode::TooLittleIn);
+        require!(amount_out >= min_amount_out, ErrorCode::SlippageExceeded);
 
         // 1) move SOL → Above vault
         let transfer_instruction = anchor_lang::solana_program::system_instruction::transfer(
@@ -90,7 +461,6 @@ pub mod ramm {
         )?;
 
         // 2) mint ACR to the user
-        let amount_out = lamports_in * DENOM / price;
         anchor_spl::token_interface::mint_to(
             CpiContext::new_with_signer(
                 ctx.accounts.token_program.to_account_info(),
@@ -108,12 +478,43 @@ pub mod ramm {
             ),
             amount_out,
         )?;
-        ctx.accounts.state.virt_above += amount_out as u128;
+        let st = &mut ctx.accounts.state;
+        st.virt_above = next_above;
+        st.total_capital = st
+            .total_capital
+            .checked_add(lamports_in)
+            .ok_or(ErrorCode::MathOverflow)?;
+
+        emit!(BuyEvent {
+            user: ctx.accounts.user.key(),
+            lamports_in,
+            amount_out,
+            price: pricing::effective_price(lamports_in, amount_out)?,
+            start_price,
+            book_value: bv,
+            floor,
+            ceil,
+            above_vault_lamports: ctx.accounts.above_vault.lamports(),
+            below_vault_lamports: ctx.accounts.below_vault.lamports(),
+            virt_above: ctx.accounts.state.virt_above,
+            virt_below: ctx.accounts.state.virt_below,
+        });
         Ok(())
     }
 
     // ----------------------------------------------------------------------
-    pub fn sell(ctx: Context<Trade>, amount_in: u64) -> Result<()> {
+    pub fn sell(
+        ctx: Context<Trade>,
+        amount_in: u64,
+        min_lamports_out: u64,
+        deadline: i64,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.state.paused & PAUSE_TRADING == 0,
+            ErrorCode::Paused
+        );
+        let now = Clock::get()?.unix_timestamp;
+        require!(now <= deadline, ErrorCode::DeadlineExceeded);
         require!(
             ctx.accounts.mint.key() == ctx.accounts.state.mint,
             ErrorCode::WrongMint
@@ -135,23 +536,37 @@ pub mod ramm {
         );
 
         // --- Clamp price to Book Value FLOOR (BV * (1 - buffer))
-        let (bv, floor, _ceil) = book_value_and_bounds(&ctx)?;
-        let mut price = sell_price_virtual(&ctx.accounts.state)?;
-        if price > floor {
-            price = floor;
-        }
-        let lamports_out = amount_in * price / DENOM;
-        // --- Solvency check (leave 1 lamport to keep PDA alive)
+        let (bv, floor, ceil) = book_value_and_bounds(&ctx)?;
+        ratchet_state(&mut ctx.accounts.state, floor, ceil, now);
+        require!(amount_in > 0, ErrorCode::TooLittleIn);
+        let (start_price, lamports_out, next_below) =
+            quote_sell_curve(&ctx.accounts.state, floor, amount_in)?;
+        require!(
+            lamports_out >= min_lamports_out,
+            ErrorCode::SlippageExceeded
+        );
+        // --- Solvency check: both vaults together cover the payout while
+        // each stays rent-exempt
+        let reserve = Rent::get()?.minimum_balance(0);
+        let above = ctx.accounts.above_vault.lamports();
+        let below = ctx.accounts.below_vault.lamports();
         require!(
-            ctx.accounts.below_vault.lamports() >= lamports_out.saturating_add(1),
+            above as u128 + below as u128 >= lamports_out as u128 + 2 * reserve as u128,
             ErrorCode::InsufficientVaultBalance
         );
         // --- MCR gate: enforce post-trade Capital Pool >= MCR (i.e., MCR% >= 100%)
-        // New pool balance after paying out this redemption:
-        let new_liq: u128 = (ctx.accounts.above_vault.lamports() as u128)
-            .saturating_add(ctx.accounts.below_vault.lamports() as u128)
-            .saturating_sub(lamports_out as u128);
-        require!(new_liq >= ctx.accounts.state.mcr, ErrorCode::McrBreached);
+        // New pool capital after paying out this redemption:
+        let new_capital = ctx
+            .accounts
+            .state
+            .total_capital
+            .checked_sub(lamports_out)
+            .ok_or(ErrorCode::InsufficientVaultBalance)?;
+        let st = &mut ctx.accounts.state;
+        refresh_mcr(st, ctx.accounts.actuary_config.as_deref())?;
+        require!(new_capital as u128 >= st.mcr, ErrorCode::McrBreached);
+        // --- Circuit breaker: cap SOL released and ACR burned per rolling day
+        check_circuit_breaker(&mut ctx.accounts.state, now, lamports_out, amount_in)?;
 
         // 1) burn ACR
         anchor_spl::token_interface::burn(
@@ -166,30 +581,51 @@ pub mod ramm {
             amount_in,
         )?;
 
-        // 2) transfer SOL ← Below vault
+        // 2) rebalance: top up Below from Above when it can't pay alone
         let mint_key = ctx.accounts.mint.key();
-        let seeds = &[BELOW_SEED, mint_key.as_ref()];
-        let signer_seeds = &[&seeds[..]];
-        let transfer_instruction = anchor_lang::solana_program::system_instruction::transfer(
-            &ctx.accounts.below_vault.key(),
-            &ctx.accounts.user.key(),
+        let need = lamports_out + reserve;
+        if below < need {
+            let shortfall = need - below;
+            vault_transfer(
+                &ctx.accounts.above_vault,
+                &ctx.accounts.below_vault,
+                &ctx.accounts.system_program,
+                &[ABOVE_SEED, mint_key.as_ref(), &[ctx.bumps.above_vault]],
+                shortfall,
+            )?;
+            emit!(VaultRebalanced {
+                lamports: shortfall,
+                above_vault_lamports: ctx.accounts.above_vault.lamports(),
+                below_vault_lamports: ctx.accounts.below_vault.lamports(),
+            });
+        }
+
+        // 3) transfer SOL ← Below vault
+        vault_transfer(
+            &ctx.accounts.below_vault,
+            &ctx.accounts.user,
+            &ctx.accounts.system_program,
+            &[BELOW_SEED, mint_key.as_ref(), &[ctx.bumps.below_vault]],
             lamports_out,
-        );
-        anchor_lang::solana_program::program::invoke_signed(
-            &transfer_instruction,
-            &[
-                ctx.accounts.below_vault.to_account_info(),
-                ctx.accounts.user.to_account_info(),
-                ctx.accounts.system_program.to_account_info(),
-            ],
-            signer_seeds,
         )?;
-        ctx.accounts.state.virt_below = ctx
-            .accounts
-            .state
-            .virt_below
-            .checked_sub(amount_in as u128)
-            .ok_or(ErrorCode::Underflow)?; // NEW: safe math
+        let st = &mut ctx.accounts.state;
+        st.virt_below = next_below;
+        st.total_capital = new_capital;
+
+        emit!(SellEvent {
+            user: ctx.accounts.user.key(),
+            amount_in,
+            lamports_out,
+            price: pricing::effective_price(lamports_out, amount_in)?,
+            start_price,
+            book_value: bv,
+            floor,
+            ceil,
+            above_vault_lamports: ctx.accounts.above_vault.lamports(),
+            below_vault_lamports: ctx.accounts.below_vault.lamports(),
+            virt_above: ctx.accounts.state.virt_above,
+            virt_below: ctx.accounts.state.virt_below,
+        });
         Ok(())
     }
 
@@ -201,15 +637,19 @@ pub mod ramm {
         if elapsed <= 0 {
             return Ok(());
         }
-        // Linearized per-second drift from daily BPS (MVP-safe; can switch to exp later)
-        let rate_num: u128 = (ctx.accounts.state.ratchet_bps_per_day as u128) * (elapsed as u128);
-        let rate_den: u128 = (10_000u128) * (SECONDS_PER_DAY as u128);
-        // Above grows by +rate, Below shrinks by -rate
-        let inc_above = ctx.accounts.state.virt_above.saturating_mul(rate_num) / rate_den;
-        let dec_below = ctx.accounts.state.virt_below.saturating_mul(rate_num) / rate_den;
-        ctx.accounts.state.virt_above = ctx.accounts.state.virt_above.saturating_add(inc_above);
-        ctx.accounts.state.virt_below = ctx.accounts.state.virt_below.saturating_sub(dec_below);
-        ctx.accounts.state.last_ratchet = now;
+        // Nothing to converge to until the pool has supply
+        if ctx.accounts.mint.supply == 0 {
+            ctx.accounts.state.last_ratchet = now;
+            return Ok(());
+        }
+        // Targets from actual capital: Above → BV*(1+buf), Below → BV*(1-buf)
+        let (_bv, floor, ceil) = book_value_and_bounds_from_accounts(
+            &ctx.accounts.state,
+            &ctx.accounts.mint,
+            &ctx.accounts.above_vault,
+            &ctx.accounts.below_vault,
+        )?;
+        ratchet_state(&mut ctx.accounts.state, floor, ceil, now);
         Ok(())
     }
 
@@ -217,17 +657,16 @@ pub mod ramm {
     // READ-ONLY QUOTES (no state changes): emit events with price & output.
     pub fn quote_buy(ctx: Context<QuoteBuy>, lamports_in: u64) -> Result<()> {
         // clamp to BV ceiling, identical to buy()
-        let (_bv, _floor, ceil) = book_value_and_bounds_from_accounts(
+        let (_bv, floor, ceil) = book_value_and_bounds_from_accounts(
             &ctx.accounts.state,
             &ctx.accounts.mint,
             &ctx.accounts.above_vault,
             &ctx.accounts.below_vault,
         )?;
-        let mut price = buy_price_virtual(&ctx.accounts.state)?;
-        if price < ceil {
-            price = ceil;
-        }
-        let amount_out = lamports_in.saturating_mul(DENOM) / price;
+        // Price against the pending ratchet without persisting it
+        let mut st = (*ctx.accounts.state).clone();
+        apply_ratchet(&mut st, floor, ceil, Clock::get()?.unix_timestamp);
+        let (price, amount_out, _) = quote_buy_curve(&st, ceil, lamports_in)?;
         emit!(QuoteBuyEvent {
             lamports_in,
             price,
@@ -238,17 +677,15 @@ pub mod ramm {
 
     pub fn quote_sell(ctx: Context<QuoteSell>, amount_in: u64) -> Result<()> {
         // clamp to BV floor, identical to sell()
-        let (_bv, floor, _ceil) = book_value_and_bounds_from_accounts(
+        let (_bv, floor, ceil) = book_value_and_bounds_from_accounts(
             &ctx.accounts.state,
             &ctx.accounts.mint,
             &ctx.accounts.above_vault,
             &ctx.accounts.below_vault,
         )?;
-        let mut price = sell_price_virtual(&ctx.accounts.state)?;
-        if price > floor {
-            price = floor;
-        }
-        let lamports_out = amount_in.saturating_mul(price) / DENOM;
+        let mut st = (*ctx.accounts.state).clone();
+        apply_ratchet(&mut st, floor, ceil, Clock::get()?.unix_timestamp);
+        let (price, lamports_out, _) = quote_sell_curve(&st, floor, amount_in)?;
         emit!(QuoteSellEvent {
             amount_in,
             price,
@@ -260,23 +697,236 @@ pub mod ramm {
 
 // === helpers =============================================================
 fn book_value_and_bounds(ctx: &Context<Trade>) -> Result<(u64, u64, u64)> {
-    let liq = (ctx.accounts.above_vault.lamports() as u128)
-        .saturating_add(ctx.accounts.below_vault.lamports() as u128);
-    let supply = ctx.accounts.mint.supply as u128;
-    require!(supply > 0, ErrorCode::TooLittleIn);
-    let bv = (liq.saturating_mul(DENOM as u128) / supply) as u64; // lamports per 1 token unit (1e9)
-    let floor = bv.saturating_mul(10_000 - ctx.accounts.state.buf_bps as u64) / 10_000;
-    let ceil = bv.saturating_mul(10_000 + ctx.accounts.state.buf_bps as u64) / 10_000;
-    Ok((bv, floor, ceil))
+    book_value_and_bounds_from_accounts(
+        &ctx.accounts.state,
+        &ctx.accounts.mint,
+        &ctx.accounts.above_vault,
+        &ctx.accounts.below_vault,
+    )
+}
+// Buy on the curve from the Above price, clamped up to the BV ceiling.
+// Returns (start price, tokens out, next Above price).
+fn quote_buy_curve(s: &State, ceil: u64, lamports_in: u64) -> Result<(u64, u64, u128)> {
+    let price = pricing::spot(s.virt_above)?.max(ceil);
+    let (out, next) = pricing::buy_on_curve(lamports_in, price, s.virt_liquidity)?;
+    Ok((price, out, next))
+}
+// Sell on the curve from the Below price, clamped down to the BV floor.
+// Returns (start price, lamports out, next Below price).
+fn quote_sell_curve(s: &State, floor: u64, amount_in: u64) -> Result<(u64, u64, u128)> {
+    let price = pricing::spot(s.virt_below)?.min(floor);
+    let (out, next) = pricing::sell_on_curve(amount_in, price, s.virt_liquidity)?;
+    Ok((price, out, next))
+}
+
+// Move the virtual prices toward [floor, ceil] for the time elapsed since the
+// last ratchet. Per-second compounding of the daily BPS rate means skipped
+// calls catch up exactly; each price stops once it reaches its target.
+// Pure over `State`, so quotes can run it on a copy. Returns seconds applied.
+fn apply_ratchet(s: &mut State, floor: u64, ceil: u64, now: i64) -> i64 {
+    let elapsed = now - s.last_ratchet;
+    if elapsed <= 0 {
+        return 0;
+    }
+    let rate = s.ratchet_bps_per_day;
+    s.virt_above = math::ratchet_toward(s.virt_above, ceil as u128, rate, elapsed as u64);
+    s.virt_below = math::ratchet_toward(s.virt_below, floor as u128, rate, elapsed as u64);
+    s.last_ratchet = now;
+    elapsed
+}
+
+// Persisting variant used by `ratchet`, `buy` and `sell`
+fn ratchet_state(s: &mut State, floor: u64, ceil: u64, now: i64) {
+    let elapsed = apply_ratchet(s, floor, ceil, now);
+    if elapsed > 0 {
+        emit!(RatchetEvent {
+            elapsed,
+            virt_above: s.virt_above,
+            virt_below: s.virt_below,
+            target_above: ceil,
+            target_below: floor,
+            timestamp: now,
+        });
+    }
+}
+
+// Start both virtual prices at `start_price` and add the seed to capital.
+// Capital left over from an earlier supply (or injected before seeding) stays
+// on the books.
+fn seed_state(s: &mut State, lamports: u64, start_price: u64, now: i64) -> Result<()> {
+    s.total_capital = s
+        .total_capital
+        .checked_add(lamports)
+        .ok_or(ErrorCode::MathOverflow)?;
+    s.virt_above = start_price as u128;
+    s.virt_below = start_price as u128;
+    s.last_ratchet = now;
+    Ok(())
+}
+
+// Re-derive MCR from live actuary cover while syncing is enabled, so a stale
+// sync can't let capital drain below what is actually in force
+fn refresh_mcr(s: &mut State, actuary_config: Option<&actuary::Config>) -> Result<()> {
+    if s.mcr_capital_factor > 0 {
+        let cfg = actuary_config.ok_or(ErrorCode::ActuaryConfigMissing)?;
+        s.mcr = mcr_for_cover(cfg.total_active_cover, s.mcr_capital_factor)?;
+    }
+    Ok(())
+}
+
+// MCR implied by `active_cover` USDC base units of actuary cover
+fn mcr_for_cover(active_cover: u64, mcr_capital_factor: u64) -> Result<u128> {
+    Ok((active_cover as u128)
+        .checked_mul(mcr_capital_factor as u128)
+        .ok_or(ErrorCode::MathOverflow)?
+        / DENOM as u128)
+}
+
+// Start a fresh one-day liquidity window once the current one has passed
+fn roll_flow_window(s: &mut State, now: i64) {
+    if now - s.flow_window_start >= SECONDS_PER_DAY {
+        s.flow_window_start = now;
+        s.injected_in_window = 0;
+        s.extracted_in_window = 0;
+    }
+}
+
+// Count an injection against the daily limit and book it as capital
+fn record_inject(s: &mut State, now: i64, lamports: u64) -> Result<()> {
+    roll_flow_window(s, now);
+    let injected = s
+        .injected_in_window
+        .checked_add(lamports)
+        .ok_or(ErrorCode::MathOverflow)?;
+    require!(
+        injected <= s.daily_inject_limit,
+        ErrorCode::RateLimitExceeded
+    );
+    s.injected_in_window = injected;
+    s.total_capital = s
+        .total_capital
+        .checked_add(lamports)
+        .ok_or(ErrorCode::MathOverflow)?;
+    Ok(())
 }
-fn book_value_virtual(s: &State) -> u64 {
-    ((s.virt_above + s.virt_below) / 2) as u64
+
+// Count an extraction against the daily limit, failing if the capital left
+// behind would not cover MCR
+fn record_extract(s: &mut State, now: i64, lamports: u64) -> Result<()> {
+    roll_flow_window(s, now);
+    let extracted = s
+        .extracted_in_window
+        .checked_add(lamports)
+        .ok_or(ErrorCode::MathOverflow)?;
+    require!(
+        extracted <= s.daily_extract_limit,
+        ErrorCode::RateLimitExceeded
+    );
+    // --- MCR gate: capital left behind must still cover MCR
+    let new_capital = s
+        .total_capital
+        .checked_sub(lamports)
+        .ok_or(ErrorCode::InsufficientVaultBalance)?;
+    require!(new_capital as u128 >= s.mcr, ErrorCode::McrBreached);
+    s.extracted_in_window = extracted;
+    s.total_capital = new_capital;
+    Ok(())
+}
+
+// Drain Above first, then Below, keeping both vaults rent-exempt.
+// Returns `(from_above, from_below)`.
+fn split_extract(above: u64, below: u64, reserve: u64, lamports: u64) -> Result<(u64, u64)> {
+    let from_above = above.saturating_sub(reserve).min(lamports);
+    let from_below = lamports - from_above;
+    require!(
+        below >= from_below.saturating_add(reserve),
+        ErrorCode::InsufficientVaultBalance
+    );
+    Ok((from_above, from_below))
 }
-fn buy_price_virtual(s: &State) -> Result<u64> {
-    Ok(book_value_virtual(s) * (10_000 + s.buf_bps as u64) / 10_000)
+
+// Drain a breaker counter by `cap` per day of `elapsed` time; an uncapped
+// counter is not tracked
+fn decay_counter(used: u64, cap: u64, elapsed: i64) -> u64 {
+    if cap == 0 {
+        return 0;
+    }
+    let drained = cap as u128 * elapsed.max(0) as u128 / SECONDS_PER_DAY as u128;
+    used.saturating_sub(drained.min(u64::MAX as u128) as u64)
 }
-fn sell_price_virtual(s: &State) -> Result<u64> {
-    Ok(book_value_virtual(s) * (10_000 - s.buf_bps as u64) / 10_000)
+
+// Count a sell against the breaker, failing once a cap is hit. Counters decay
+// linearly, so a burst is limited to the cap and sustained outflow to the cap
+// per day, with no window edge to trade across.
+fn check_circuit_breaker(s: &mut State, now: i64, lamports_out: u64, amount_in: u64) -> Result<()> {
+    let elapsed = now - s.breaker_updated_at;
+    s.sol_released_recent = decay_counter(s.sol_released_recent, s.sol_release_cap, elapsed);
+    s.acr_burned_recent = decay_counter(s.acr_burned_recent, s.acr_burn_cap, elapsed);
+    s.breaker_updated_at = now;
+    let released = s
+        .sol_released_recent
+        .checked_add(lamports_out)
+        .ok_or(ErrorCode::MathOverflow)?;
+    let burned = s
+        .acr_burned_recent
+        .checked_add(amount_in)
+        .ok_or(ErrorCode::MathOverflow)?;
+    require!(
+        s.sol_release_cap == 0 || released <= s.sol_release_cap,
+        ErrorCode::CircuitBreakerTripped
+    );
+    require!(
+        s.acr_burn_cap == 0 || burned <= s.acr_burn_cap,
+        ErrorCode::CircuitBreakerTripped
+    );
+    s.sol_released_recent = released;
+    s.acr_burned_recent = burned;
+    Ok(())
+}
+
+// Make the pending authority the authority, moving the guardian role over too
+// if the old key still held it. Returns the old authority.
+fn accept_pending_authority(s: &mut State) -> Pubkey {
+    let previous = s.authority;
+    if s.guardian == previous {
+        s.guardian = s.pending_authority;
+    }
+    s.authority = s.pending_authority;
+    s.pending_authority = Pubkey::default();
+    previous
+}
+
+fn reset_breaker(s: &mut State, now: i64) {
+    s.breaker_updated_at = now;
+    s.sol_released_recent = 0;
+    s.acr_burned_recent = 0;
+}
+
+// Move lamports out of a vault PDA, signed with the vault's own seeds
+fn vault_transfer<'info>(
+    from: &AccountInfo<'info>,
+    to: &AccountInfo<'info>,
+    system_program: &Program<'info, System>,
+    seeds: &[&[u8]],
+    lamports: u64,
+) -> Result<()> {
+    let transfer_instruction =
+        anchor_lang::solana_program::system_instruction::transfer(from.key, to.key, lamports);
+    anchor_lang::solana_program::program::invoke_signed(
+        &transfer_instruction,
+        &[from.clone(), to.clone(), system_program.to_account_info()],
+        &[seeds],
+    )?;
+    Ok(())
+}
+
+fn validate_params(p: &RammParams) -> Result<()> {
+    require!(p.buf_bps < 10_000, ErrorCode::InvalidParams);
+    // below price decays by this rate, so it must stay under 100%/day
+    require!(p.ratchet_bps_per_day < 10_000, ErrorCode::InvalidParams);
+    // the curve needs a non-empty virtual reserve
+    require!(p.virt_liquidity > 0, ErrorCode::InvalidParams);
+    Ok(())
 }
 
 // Same BV/bounds computation but for quote contexts (no &Context<Trade>)
@@ -286,13 +936,9 @@ fn book_value_and_bounds_from_accounts(
     above_vault: &AccountInfo,
     below_vault: &AccountInfo,
 ) -> Result<(u64, u64, u64)> {
-    let liq = (above_vault.lamports() as u128).saturating_add(below_vault.lamports() as u128);
-    let supply = mint.supply as u128;
-    require!(supply > 0, ErrorCode::TooLittleIn);
-    let bv = (liq.saturating_mul(DENOM as u128) / supply) as u64;
-    let floor = bv.saturating_mul(10_000 - state.buf_bps as u64) / 10_000;
-    let ceil = bv.saturating_mul(10_000 + state.buf_bps as u64) / 10_000;
-    Ok((bv, floor, ceil))
+    let liq = above_vault.lamports() as u128 + below_vault.lamports() as u128;
+    // lamports per 1 token unit (1e9)
+    pricing::bounds(liq, mint.supply, state.buf_bps)
 }
 
 // === contexts (only mint check added) ======================================
@@ -300,6 +946,16 @@ fn book_value_and_bounds_from_accounts(
 pub struct Init<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
+    // Only the program's upgrade authority may create pools, so nobody can
+    // front-run init and take over a mint's pool
+    pub authority: Signer<'info>,
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, crate::program::Ramm>,
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(authority.key())
+            @ ErrorCode::Unauthorized
+    )]
+    pub program_data: Account<'info, ProgramData>,
     // Create State PDA (seeded by mint) so mint-authority PDA is unique per mint
     #[account(
         init,
@@ -315,6 +971,26 @@ pub struct Init<'info> {
             + 16   // virt_above
             + 16   // virt_below
             + 8    // last_ratchet
+            + 32   // authority
+            + 8    // param_timelock
+            + (2 + 2 + 16 + 8 + 8 + 8 + 8 + 8 + 8) // pending_params
+            + 8    // pending_eta
+            + 8    // mcr_capital_factor
+            + 8    // total_capital
+            + 8    // virt_liquidity
+            + 8    // daily_inject_limit
+            + 8    // daily_extract_limit
+            + 8    // flow_window_start
+            + 8    // injected_in_window
+            + 8    // extracted_in_window
+            + 8    // sol_release_cap
+            + 8    // acr_burn_cap
+            + 8    // breaker_updated_at
+            + 8    // sol_released_recent
+            + 8    // acr_burned_recent
+            + 32   // guardian
+            + 1    // paused
+            + 32   // pending_authority
     )]
     pub state: Account<'info, State>,
     // Create System-owned zero-data vault PDAs to hold SOL
@@ -343,6 +1019,61 @@ pub struct Init<'info> {
     pub token_program: Interface<'info, TokenInterface>,
 }
 
+#[derive(Accounts)]
+pub struct SeedLiquidity<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds=[STATE_SEED, mint.key().as_ref()],
+        bump = state.bump,
+        has_one = authority
+    )]
+    pub state: Account<'info, State>,
+    #[account(mut, seeds=[ABOVE_SEED, mint.key().as_ref()], bump)]
+    /// CHECK: lamport vault
+    pub above_vault: AccountInfo<'info>,
+    #[account(mut, seeds=[BELOW_SEED, mint.key().as_ref()], bump)]
+    /// CHECK: lamport vault
+    pub below_vault: AccountInfo<'info>,
+    #[account(mut, address = state.mint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(mut, token::mint = mint)]
+    pub authority_ata: InterfaceAccount<'info, TokenAccount>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct ManageLiquidity<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds=[STATE_SEED, mint.key().as_ref()],
+        bump = state.bump,
+        has_one = authority
+    )]
+    pub state: Account<'info, State>,
+    #[account(mut, seeds=[ABOVE_SEED, mint.key().as_ref()], bump)]
+    /// CHECK: lamport vault
+    pub above_vault: AccountInfo<'info>,
+    #[account(mut, seeds=[BELOW_SEED, mint.key().as_ref()], bump)]
+    /// CHECK: lamport vault
+    pub below_vault: AccountInfo<'info>,
+    #[account(address = state.mint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    /// actuary's global Config; extraction sizes MCR from it while
+    /// mcr_capital_factor > 0, otherwise it may be omitted
+    #[account(
+        seeds = [b"config"],
+        bump = actuary_config.bump,
+        seeds::program = actuary::ID
+    )]
+    pub actuary_config: Option<Account<'info, actuary::Config>>,
+    pub system_program: Program<'info, System>,
+}
+
 #[derive(Accounts)]
 pub struct Trade<'info> {
     #[account(mut)]
@@ -360,6 +1091,14 @@ pub struct Trade<'info> {
     /// NEW: passed mint must equal state.mint
     #[account(address = state.mint)]
     pub mint: InterfaceAccount<'info, Mint>,
+    /// actuary's global Config; sells size MCR from it while
+    /// mcr_capital_factor > 0. Buys never read it and may omit it.
+    #[account(
+        seeds = [b"config"],
+        bump = actuary_config.bump,
+        seeds::program = actuary::ID
+    )]
+    pub actuary_config: Option<Account<'info, actuary::Config>>,
     pub system_program: Program<'info, System>,
     pub token_program: Interface<'info, TokenInterface>,
 }
@@ -380,6 +1119,72 @@ pub struct Ratchet<'info> {
     pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+pub struct UpdateParams<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds=[STATE_SEED, mint.key().as_ref()],
+        bump = state.bump,
+        has_one = authority
+    )]
+    pub state: Account<'info, State>,
+    #[account(address = state.mint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAuthority<'info> {
+    pub pending_authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds=[STATE_SEED, mint.key().as_ref()],
+        bump = state.bump,
+        has_one = pending_authority
+    )]
+    pub state: Account<'info, State>,
+    #[account(address = state.mint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+}
+
+#[derive(Accounts)]
+pub struct Pause<'info> {
+    pub signer: Signer<'info>,
+    #[account(
+        mut,
+        seeds=[STATE_SEED, mint.key().as_ref()],
+        bump = state.bump,
+        constraint = signer.key() == state.authority || signer.key() == state.guardian
+            @ ErrorCode::Unauthorized
+    )]
+    pub state: Account<'info, State>,
+    #[account(address = state.mint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+}
+
+#[derive(Accounts)]
+pub struct ApplyParams<'info> {
+    #[account(mut, seeds=[STATE_SEED, mint.key().as_ref()], bump = state.bump)]
+    pub state: Account<'info, State>,
+    #[account(address = state.mint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+}
+
+#[derive(Accounts)]
+pub struct SyncMcr<'info> {
+    #[account(mut, seeds=[STATE_SEED, mint.key().as_ref()], bump = state.bump)]
+    pub state: Account<'info, State>,
+    #[account(address = state.mint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    /// actuary's global Config, source of total_active_cover
+    #[account(
+        seeds = [b"config"],
+        bump = actuary_config.bump,
+        seeds::program = actuary::ID
+    )]
+    pub actuary_config: Account<'info, actuary::Config>,
+}
+
 #[derive(Accounts)]
 pub struct QuoteBuy<'info> {
     #[account(seeds=[STATE_SEED, mint.key().as_ref()], bump = state.bump)]
@@ -423,6 +1228,127 @@ pub struct QuoteSellEvent {
     pub lamports_out: u64,
 }
 
+// Executed trades; vault balances and virtual prices are post-trade
+#[event]
+pub struct BuyEvent {
+    pub user: Pubkey,
+    pub lamports_in: u64,
+    pub amount_out: u64,
+    pub price: u64, // executed lamports per 1 ACR (1e9 units), averaged over the curve
+    pub start_price: u64, // clamped spot price the curve started from
+    pub book_value: u64,
+    pub floor: u64,
+    pub ceil: u64,
+    pub above_vault_lamports: u64,
+    pub below_vault_lamports: u64,
+    pub virt_above: u128,
+    pub virt_below: u128,
+}
+
+#[event]
+pub struct SellEvent {
+    pub user: Pubkey,
+    pub amount_in: u64,
+    pub lamports_out: u64,
+    pub price: u64, // executed lamports per 1 ACR (1e9 units), averaged over the curve
+    pub start_price: u64, // clamped spot price the curve started from
+    pub book_value: u64,
+    pub floor: u64,
+    pub ceil: u64,
+    pub above_vault_lamports: u64,
+    pub below_vault_lamports: u64,
+    pub virt_above: u128,
+    pub virt_below: u128,
+}
+
+#[event]
+pub struct RatchetEvent {
+    pub elapsed: i64,
+    pub virt_above: u128,
+    pub virt_below: u128,
+    pub target_above: u64,
+    pub target_below: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ParamsQueued {
+    pub params: RammParams,
+    pub eta: i64,
+}
+
+#[event]
+pub struct ParamsApplied {
+    pub params: RammParams,
+}
+
+#[event]
+pub struct ParamsCancelled {}
+
+#[event]
+pub struct VaultRebalanced {
+    pub lamports: u64,
+    pub above_vault_lamports: u64,
+    pub below_vault_lamports: u64,
+}
+
+#[event]
+pub struct AuthorityProposed {
+    pub authority: Pubkey,
+    pub pending_authority: Pubkey,
+}
+
+#[event]
+pub struct AuthorityAccepted {
+    pub previous: Pubkey,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct GuardianUpdated {
+    pub guardian: Pubkey,
+}
+
+#[event]
+pub struct PauseUpdated {
+    pub paused: u8,
+    pub by: Pubkey,
+}
+
+#[event]
+pub struct CircuitBreakerReset {
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LiquidityInjected {
+    pub lamports: u64,
+    pub total_capital: u64,
+    pub injected_in_window: u64,
+}
+
+#[event]
+pub struct LiquidityExtracted {
+    pub lamports: u64,
+    pub total_capital: u64,
+    pub extracted_in_window: u64,
+}
+
+#[event]
+pub struct LiquiditySeeded {
+    pub authority: Pubkey,
+    pub lamports: u64,
+    pub start_price: u64,
+    pub amount: u64,
+}
+
+#[event]
+pub struct McrSynced {
+    pub total_active_cover: u64,
+    pub mcr_capital_factor: u64,
+    pub mcr: u128,
+}
+
 #[error_code]
 pub enum ErrorCode {
     #[msg("Input too small")]
@@ -439,4 +1365,266 @@ pub enum ErrorCode {
     InsufficientVaultBalance,
     #[msg("Sell would breach MCR (post-trade capital < MCR)")]
     McrBreached,
+    #[msg("Output below the caller's minimum")]
+    SlippageExceeded,
+    #[msg("Transaction executed after its deadline")]
+    DeadlineExceeded,
+    #[msg("Parameter out of bounds")]
+    InvalidParams,
+    #[msg("No parameter change queued")]
+    NoPendingParams,
+    #[msg("Parameter timelock has not elapsed")]
+    TimelockActive,
+    #[msg("Arithmetic overflow")]
+    MathOverflow,
+    #[msg("MCR sync disabled (capital factor is zero)")]
+    McrSyncDisabled,
+    #[msg("Pool already has supply")]
+    AlreadySeeded,
+    #[msg("Virtual liquidity too shallow for this price")]
+    InsufficientLiquidity,
+    #[msg("Daily liquidity limit exceeded")]
+    RateLimitExceeded,
+    #[msg("Sell outflow cap reached for this window")]
+    CircuitBreakerTripped,
+    #[msg("Trading is paused")]
+    Paused,
+    #[msg("Signer is neither authority nor guardian")]
+    Unauthorized,
+    #[msg("actuary Config is required while MCR follows actuary cover")]
+    ActuaryConfigMissing,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CAP: u64 = 1_000;
+
+    fn breaker_state() -> State {
+        State {
+            sol_release_cap: CAP,
+            acr_burn_cap: 10 * CAP,
+            ..State::default()
+        }
+    }
+
+    #[test]
+    fn breaker_trips_once_the_cap_is_used() {
+        let mut s = breaker_state();
+        check_circuit_breaker(&mut s, 0, CAP, 1).unwrap();
+        assert!(check_circuit_breaker(&mut s, 0, 1, 1).is_err());
+        // A failed sell is not counted
+        assert_eq!(s.sol_released_recent, CAP);
+    }
+
+    #[test]
+    fn breaker_trips_on_the_burn_cap_alone() {
+        let mut s = breaker_state();
+        assert!(check_circuit_breaker(&mut s, 0, 1, 10 * CAP + 1).is_err());
+    }
+
+    #[test]
+    fn breaker_has_no_window_edge_to_straddle() {
+        // Draining the cap just before a day boundary leaves nothing just after it
+        let mut s = breaker_state();
+        check_circuit_breaker(&mut s, SECONDS_PER_DAY - 1, CAP, 1).unwrap();
+        assert!(check_circuit_breaker(&mut s, SECONDS_PER_DAY + 1, CAP, 1).is_err());
+    }
+
+    #[test]
+    fn breaker_capacity_returns_linearly() {
+        let mut s = breaker_state();
+        check_circuit_breaker(&mut s, 0, CAP, 1).unwrap();
+        // Half a day frees exactly half the cap
+        let half = SECONDS_PER_DAY / 2;
+        assert!(check_circuit_breaker(&mut s, half, CAP / 2 + 1, 1).is_err());
+        check_circuit_breaker(&mut s, half, CAP / 2, 1).unwrap();
+        // A full day after the last sell the whole cap is available again
+        check_circuit_breaker(&mut s, half + SECONDS_PER_DAY, CAP, 1).unwrap();
+    }
+
+    #[test]
+    fn uncapped_breaker_never_trips() {
+        let mut s = State::default();
+        check_circuit_breaker(&mut s, 0, u64::MAX, u64::MAX).unwrap();
+        check_circuit_breaker(&mut s, 0, u64::MAX, u64::MAX).unwrap();
+    }
+
+    #[test]
+    fn reset_reopens_a_tripped_breaker() {
+        let mut s = breaker_state();
+        check_circuit_breaker(&mut s, 0, CAP, 1).unwrap();
+        assert!(check_circuit_breaker(&mut s, 10, CAP, 1).is_err());
+        reset_breaker(&mut s, 10);
+        check_circuit_breaker(&mut s, 10, CAP, 1).unwrap();
+    }
+    #[test]
+    fn accepting_authority_strips_the_guardian_role_from_the_old_key() {
+        let (old, new, other) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let mut s = State {
+            authority: old,
+            guardian: old,
+            pending_authority: new,
+            ..State::default()
+        };
+        assert_eq!(accept_pending_authority(&mut s), old);
+        assert_eq!((s.authority, s.guardian), (new, new));
+        assert_eq!(s.pending_authority, Pubkey::default());
+
+        // A separately assigned guardian is left alone
+        s.guardian = other;
+        s.pending_authority = old;
+        accept_pending_authority(&mut s);
+        assert_eq!((s.authority, s.guardian), (old, other));
+    }
+    #[test]
+    fn seeding_sets_prices_and_books_the_deposit() {
+        let mut s = State::default();
+        seed_state(&mut s, 5_000, 1_000_000_000, 42).unwrap();
+        assert_eq!((s.virt_above, s.virt_below), (1_000_000_000, 1_000_000_000));
+        assert_eq!((s.total_capital, s.last_ratchet), (5_000, 42));
+    }
+
+    #[test]
+    fn seeding_keeps_existing_capital_on_the_books() {
+        let mut s = State {
+            total_capital: 7_000,
+            ..State::default()
+        };
+        seed_state(&mut s, 5_000, 1, 0).unwrap();
+        assert_eq!(s.total_capital, 12_000);
+
+        s.total_capital = u64::MAX;
+        assert!(seed_state(&mut s, 1, 1, 0).is_err());
+    }
+    fn flow_state() -> State {
+        State {
+            daily_inject_limit: 1_000,
+            daily_extract_limit: 1_000,
+            total_capital: 10_000,
+            ..State::default()
+        }
+    }
+
+    fn is_error(res: Result<()>, code: ErrorCode) -> bool {
+        res.unwrap_err() == code.into()
+    }
+
+    #[test]
+    fn flow_window_resets_only_after_a_full_day() {
+        let mut s = State {
+            flow_window_start: 100,
+            injected_in_window: 5,
+            extracted_in_window: 7,
+            ..State::default()
+        };
+        roll_flow_window(&mut s, 100 + SECONDS_PER_DAY - 1);
+        assert_eq!((s.flow_window_start, s.injected_in_window), (100, 5));
+        roll_flow_window(&mut s, 100 + SECONDS_PER_DAY);
+        assert_eq!(s.flow_window_start, 100 + SECONDS_PER_DAY);
+        assert_eq!((s.injected_in_window, s.extracted_in_window), (0, 0));
+    }
+
+    #[test]
+    fn inject_fails_past_the_daily_limit() {
+        let mut s = flow_state();
+        record_inject(&mut s, 0, 1_000).unwrap();
+        assert_eq!(s.total_capital, 11_000);
+        assert!(is_error(
+            record_inject(&mut s, 10, 1),
+            ErrorCode::RateLimitExceeded
+        ));
+        record_inject(&mut s, SECONDS_PER_DAY, 1_000).unwrap();
+    }
+
+    #[test]
+    fn extract_fails_past_the_daily_limit() {
+        let mut s = flow_state();
+        record_extract(&mut s, 0, 600).unwrap();
+        assert!(is_error(
+            record_extract(&mut s, 10, 401),
+            ErrorCode::RateLimitExceeded
+        ));
+        assert_eq!((s.total_capital, s.extracted_in_window), (9_400, 600));
+    }
+
+    #[test]
+    fn extract_never_leaves_capital_below_mcr() {
+        let mut s = State {
+            mcr: 9_500,
+            ..flow_state()
+        };
+        assert!(is_error(
+            record_extract(&mut s, 0, 501),
+            ErrorCode::McrBreached
+        ));
+        // A failed extraction does not use up the daily limit
+        assert_eq!(s.extracted_in_window, 0);
+        record_extract(&mut s, 0, 500).unwrap();
+        assert_eq!(s.total_capital, 9_500);
+    }
+
+    #[test]
+    fn extract_keeps_both_vaults_rent_exempt() {
+        // Above pays what it holds over the reserve, Below the rest
+        assert_eq!(split_extract(300, 1_000, 100, 500).unwrap(), (200, 300));
+        assert_eq!(split_extract(50, 1_000, 100, 500).unwrap(), (0, 500));
+        assert_eq!(split_extract(300, 400, 100, 500).unwrap(), (200, 300));
+        assert!(split_extract(300, 399, 100, 500).is_err());
+    }
+    fn actuary_config(total_active_cover: u64) -> actuary::Config {
+        actuary::Config {
+            admin: Pubkey::default(),
+            underwriter: Pubkey::default(),
+            pauser: Pubkey::default(),
+            usdc_mint: Pubkey::default(),
+            bump: 0,
+            unstake_cooldown: 0,
+            total_staked: 0,
+            total_active_cover,
+            leverage_bps: 0,
+            voting_period: 0,
+            quorum_bps: 0,
+            paused: 0,
+            pending_admin: Pubkey::default(),
+            total_shares: 0,
+            stake_index: 0,
+            pending_shares: 0,
+            stake_epoch: 0,
+        }
+    }
+
+    #[test]
+    fn extract_is_gated_on_live_actuary_cover() {
+        // The last sync saw no cover, but 9_500 units are now in force
+        let mut s = State {
+            mcr_capital_factor: DENOM,
+            ..flow_state()
+        };
+        refresh_mcr(&mut s, Some(&actuary_config(9_500))).unwrap();
+        assert_eq!(s.mcr, 9_500);
+        assert!(is_error(
+            record_extract(&mut s, 0, 501),
+            ErrorCode::McrBreached
+        ));
+        record_extract(&mut s, 0, 500).unwrap();
+    }
+
+    #[test]
+    fn live_mcr_needs_the_actuary_config_only_while_syncing() {
+        let mut s = State {
+            mcr: 42,
+            ..flow_state()
+        };
+        refresh_mcr(&mut s, None).unwrap();
+        assert_eq!(s.mcr, 42);
+
+        s.mcr_capital_factor = DENOM;
+        assert!(refresh_mcr(&mut s, None).is_err());
+    }
 }
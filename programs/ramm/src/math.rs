@@ -0,0 +1,215 @@
+// Q64.64 fixed-point helpers used to compound the ratchet rate.
+//
+// A Q64.64 value `q` represents `q / 2^64`. Multiplying an integer by a Q64.64
+// factor with `mul` yields an integer, so prices can be scaled directly.
+
+pub const ONE: u128 = 1 << 64;
+const LO_MASK: u128 = u64::MAX as u128;
+const SECONDS_PER_DAY: u128 = 86_400;
+const BPS: u128 = 10_000;
+const LN2: u128 = 0xB172_17F7_D1CF_79AB; // ln(2) in Q64.64, rounded down
+
+/// `a * b / 2^64`, rounded down; `None` if the result does not fit in u128.
+pub fn mul(a: u128, b: u128) -> Option<u128> {
+    let (a_hi, a_lo) = (a >> 64, a & LO_MASK);
+    let (b_hi, b_lo) = (b >> 64, b & LO_MASK);
+    // Each partial product of two 64-bit limbs fits in u128
+    let hh = a_hi * b_hi;
+    if hh > LO_MASK {
+        return None;
+    }
+    (hh << 64)
+        .checked_add(a_hi * b_lo)?
+        .checked_add(a_lo * b_hi)?
+        .checked_add((a_lo * b_lo) >> 64)
+}
+
+/// `base^exp` for a Q64.64 `base`, by repeated squaring.
+pub fn pow(base: u128, mut exp: u64) -> Option<u128> {
+    let mut result = ONE;
+    let mut b = base;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = mul(result, b)?;
+        }
+        exp >>= 1;
+        if exp > 0 {
+            b = mul(b, b)?;
+        }
+    }
+    Some(result)
+}
+
+/// Natural log of a Q64.64 `a` in `(0, 2)` as `(magnitude, negative)`.
+fn ln(a: u128) -> (u128, bool) {
+    // Scale into [1, 2): a = m / 2^k
+    let k = if a < ONE {
+        a.leading_zeros() - ONE.leading_zeros()
+    } else {
+        0
+    };
+    let m = a << k;
+    // ln(m) = 2 * atanh(z) with z = (m - 1) / (m + 1) in [0, 1/3)
+    let z = (m - ONE) * ONE / (m + ONE);
+    let z2 = mul(z, z).unwrap_or(0);
+    let (mut term, mut sum, mut n) = (z, 0u128, 1u128);
+    while term > 0 {
+        sum += term / n;
+        term = mul(term, z2).unwrap_or(0);
+        n += 2;
+    }
+    let ln_m = 2 * sum;
+    let k_ln2 = k as u128 * LN2;
+    if ln_m >= k_ln2 {
+        (ln_m - k_ln2, false)
+    } else {
+        (k_ln2 - ln_m, true)
+    }
+}
+
+/// `e^x` (or `e^-x` when `negative`) for a small Q64.64 `x`.
+fn exp(x: u128, negative: bool) -> u128 {
+    let (mut term, mut sum, mut k) = (ONE, ONE, 1u128);
+    loop {
+        term = mul(term, x).unwrap_or(0) / k;
+        if term == 0 {
+            return sum;
+        }
+        if negative && k % 2 == 1 {
+            sum -= term;
+        } else {
+            sum += term;
+        }
+        k += 1;
+    }
+}
+
+/// Per-second factor in Q64.64: the 86_400th root of `1 ± bps_per_day / 10_000`,
+/// so a full day of compounding moves a value by exactly the daily rate.
+pub fn per_second_factor(bps_per_day: u16, up: bool) -> u128 {
+    let rate = (bps_per_day as u128) * ONE / BPS;
+    let daily = if up { ONE + rate } else { ONE - rate };
+    if daily == 0 {
+        return 0;
+    }
+    let (ln_daily, negative) = ln(daily);
+    exp(ln_daily / SECONDS_PER_DAY, negative)
+}
+
+/// Compound `value` per second over `elapsed` seconds at `bps_per_day`,
+/// growing when `up` and decaying otherwise. Compounding per second makes
+/// one call over N days equal to N daily calls, up to rounding.
+/// Saturates at `u128::MAX` if growth overflows.
+pub fn compound(value: u128, bps_per_day: u16, elapsed: u64, up: bool) -> u128 {
+    pow(per_second_factor(bps_per_day, up), elapsed)
+        .and_then(|factor| mul(value, factor))
+        .unwrap_or(u128::MAX)
+}
+
+/// Move `value` toward `target` by compounding at `bps_per_day` for
+/// `elapsed` seconds, stopping at the target instead of overshooting it.
+pub fn ratchet_toward(value: u128, target: u128, bps_per_day: u16, elapsed: u64) -> u128 {
+    if value > target {
+        compound(value, bps_per_day, elapsed, false).max(target)
+    } else {
+        compound(value, bps_per_day, elapsed, true).min(target)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    const DAY: u64 = 86_400;
+
+    fn within(a: u128, b: u128, tol: u128) -> bool {
+        a.abs_diff(b) <= tol
+    }
+
+    #[test]
+    fn mul_identity_and_overflow() {
+        assert_eq!(mul(12_345, ONE), Some(12_345));
+        assert_eq!(mul(ONE, ONE), Some(ONE));
+        assert_eq!(mul(u128::MAX, 2 * ONE), None);
+    }
+
+    #[test]
+    fn pow_matches_repeated_mul() {
+        let f = per_second_factor(400, true);
+        assert_eq!(pow(f, 0), Some(ONE));
+        assert_eq!(pow(f, 3), Some(mul(mul(f, f).unwrap(), f).unwrap()));
+    }
+
+    #[test]
+    fn one_day_matches_the_daily_rate() {
+        let v = 1_000_000_000u128;
+        assert!(within(compound(v, 400, DAY, true), 1_040_000_000, 1));
+        assert!(within(compound(v, 400, DAY, false), 960_000_000, 1));
+    }
+
+    #[test]
+    fn zero_rate_is_flat() {
+        assert_eq!(per_second_factor(0, true), ONE);
+        assert_eq!(per_second_factor(0, false), ONE);
+    }
+
+    proptest! {
+        #[test]
+        fn one_day_compounds_to_exactly_the_daily_rate(bps in 0u16..10_000, up in any::<bool>()) {
+            let v = 1_000_000_000_000_000_000u128;
+            let expected = if up {
+                v + v * bps as u128 / BPS
+            } else {
+                v - v * bps as u128 / BPS
+            };
+            let got = compound(v, bps, DAY, up);
+            // Within 1e-12 relative of the exact daily step
+            prop_assert!(within(got, expected, v / 1_000_000_000_000), "got={} expected={}", got, expected);
+        }
+
+        #[test]
+        fn once_over_n_days_equals_n_daily_steps(
+            value in 1_000_000_000u128..1_000_000_000_000_000_000u128,
+            bps in 0u16..1_000,
+            days in 1u64..90,
+            up in any::<bool>(),
+        ) {
+            let once = compound(value, bps, days * DAY, up);
+            let mut daily = value;
+            for _ in 0..days {
+                daily = compound(daily, bps, DAY, up);
+            }
+            // Only the per-step floor differs: allow 1e-7 relative + 1 unit
+            let tol = once / 10_000_000 + 1;
+            prop_assert!(within(once, daily, tol), "once={} daily={}", once, daily);
+        }
+
+        #[test]
+        fn ratchet_toward_once_equals_daily_and_never_overshoots(
+            value in 1_000_000_000u128..1_000_000_000_000_000u128,
+            target in 1_000_000_000u128..1_000_000_000_000_000u128,
+            bps in 0u16..1_000,
+            days in 1u64..90,
+        ) {
+            let once = ratchet_toward(value, target, bps, days * DAY);
+            let mut daily = value;
+            for _ in 0..days {
+                daily = ratchet_toward(daily, target, bps, DAY);
+            }
+            let tol = once / 10_000_000 + 1;
+            prop_assert!(within(once, daily, tol), "once={} daily={}", once, daily);
+            prop_assert!(once >= value.min(target) && once <= value.max(target));
+        }
+
+        #[test]
+        fn growth_and_decay_are_monotonic(
+            value in 1u128..u64::MAX as u128,
+            bps in 0u16..10_000,
+            elapsed in 0u64..(365 * DAY),
+        ) {
+            prop_assert!(compound(value, bps, elapsed, true) >= value);
+            prop_assert!(compound(value, bps, elapsed, false) <= value);
+        }
+    }
+}
@@ -0,0 +1,220 @@
+// Checked pricing math for the ramm.
+//
+// Prices are lamports per 1e9 token units (DENOM). All intermediates are u128
+// and every narrowing back to u64 is checked. Each division rounds in the
+// pool's favour: the buy-side price rounds up, the sell-side price and every
+// trade output round down.
+//
+// Trades execute on a constant-product curve (as in Nexus RAMM v2): each side
+// holds `liquidity` lamports of virtual SOL against the token reserve implied
+// by its spot price, so the executed price integrates over the trade size.
+
+use crate::ErrorCode;
+use anchor_lang::prelude::*;
+
+const DENOM: u128 = crate::DENOM as u128;
+const BPS: u128 = 10_000;
+
+fn to_u64(v: u128) -> Result<u64> {
+    u64::try_from(v).map_err(|_| error!(ErrorCode::MathOverflow))
+}
+
+fn div_ceil(n: u128, d: u128) -> Result<u128> {
+    require!(d > 0, ErrorCode::MathOverflow);
+    Ok(n.div_ceil(d))
+}
+
+fn div_floor(n: u128, d: u128) -> Result<u128> {
+    n.checked_div(d).ok_or(error!(ErrorCode::MathOverflow))
+}
+
+fn mul(a: u128, b: u128) -> Result<u128> {
+    a.checked_mul(b).ok_or(error!(ErrorCode::MathOverflow))
+}
+
+/// Book value and its buffered bounds `(bv, floor, ceil)` for `liq` lamports
+/// of capital backing `supply` tokens. `floor` rounds down, `ceil` rounds up.
+pub fn bounds(liq: u128, supply: u64, buf_bps: u16) -> Result<(u64, u64, u64)> {
+    require!(supply > 0, ErrorCode::TooLittleIn);
+    let num = mul(liq, DENOM)?;
+    let supply = supply as u128;
+    let bv = to_u64(num / supply)?;
+    let floor = to_u64(div_floor(mul(num, BPS - buf_bps as u128)?, supply * BPS)?)?;
+    let ceil = to_u64(div_ceil(mul(num, BPS + buf_bps as u128)?, supply * BPS)?)?;
+    Ok((bv, floor, ceil))
+}
+
+/// Narrow a stored virtual price to a u64 spot price.
+pub fn spot(virt: u128) -> Result<u64> {
+    to_u64(virt)
+}
+
+/// Tokens minted for `lamports_in` at a flat `price`, rounded down.
+pub fn tokens_out(lamports_in: u64, price: u64) -> Result<u64> {
+    to_u64(div_floor(mul(lamports_in as u128, DENOM)?, price as u128)?)
+}
+
+/// Average price a trade executed at: `lamports` paid or received per 1e9 of
+/// the `tokens` it moved, rounded down.
+pub fn effective_price(lamports: u64, tokens: u64) -> Result<u64> {
+    to_u64(div_floor(mul(lamports as u128, DENOM)?, tokens as u128)?)
+}
+
+/// Buy `lamports_in` on the curve starting at spot `price`.
+/// Returns `(tokens_out, next_price)`; the next buy price rounds up.
+pub fn buy_on_curve(lamports_in: u64, price: u64, liquidity: u64) -> Result<(u64, u128)> {
+    let sol = liquidity as u128;
+    // Fewer virtual tokens means fewer tokens out
+    let tokens = div_floor(mul(sol, DENOM)?, price as u128)?;
+    require!(tokens > 0, ErrorCode::InsufficientLiquidity);
+    let sol_after = sol + lamports_in as u128;
+    let out = div_floor(mul(tokens, lamports_in as u128)?, sol_after)?;
+    let next = div_ceil(mul(sol_after, DENOM)?, tokens - out)?;
+    Ok((to_u64(out)?, next))
+}
+
+/// Sell `amount_in` tokens on the curve starting at spot `price`.
+/// Returns `(lamports_out, next_price)`; the next sell price rounds down.
+pub fn sell_on_curve(amount_in: u64, price: u64, liquidity: u64) -> Result<(u64, u128)> {
+    let sol = liquidity as u128;
+    // More virtual tokens means fewer lamports out
+    let tokens = div_ceil(mul(sol, DENOM)?, price as u128)?;
+    let tokens_after = tokens + amount_in as u128;
+    let out = div_floor(mul(sol, amount_in as u128)?, tokens_after)?;
+    let next = div_floor(mul(sol - out, DENOM)?, tokens_after)?;
+    Ok((to_u64(out)?, next))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn avg_price(lamports: u64, tokens: u64) -> u128 {
+        lamports as u128 * DENOM / tokens as u128
+    }
+
+    #[test]
+    fn bounds_reject_empty_supply() {
+        assert!(bounds(1_000, 0, 100).is_err());
+    }
+
+    #[test]
+    fn zero_price_is_an_error_not_a_panic() {
+        assert!(tokens_out(1, 0).is_err());
+        assert!(buy_on_curve(1, 0, 1_000).is_err());
+        assert!(sell_on_curve(1, 0, 1_000).is_err());
+    }
+
+    #[test]
+    fn effective_price_reflects_curve_slippage() {
+        let (price, depth) = (1_000_000_000, 1_000 * 1_000_000_000);
+        let lamports_in = 100 * 1_000_000_000;
+        let (out, _) = buy_on_curve(lamports_in, price, depth).unwrap();
+        // 10% of the depth in: pays 1.1x spot on average
+        assert_eq!(effective_price(lamports_in, out).unwrap(), 1_100_000_000);
+        assert!(effective_price(1, 0).is_err());
+    }
+
+    #[test]
+    fn large_buy_does_not_overflow() {
+        // 100k SOL at 1 SOL per token
+        let out = tokens_out(100_000 * 1_000_000_000, 1_000_000_000).unwrap();
+        assert_eq!(out, 100_000 * 1_000_000_000);
+    }
+
+    #[test]
+    fn price_moves_with_trade_size() {
+        let (price, depth) = (1_000_000_000, 1_000 * 1_000_000_000);
+        let (small, _) = buy_on_curve(1_000_000_000, price, depth).unwrap();
+        let (large, next) = buy_on_curve(10_000 * 1_000_000_000, price, depth).unwrap();
+        assert!(avg_price(1_000_000_000, small) < avg_price(10_000 * 1_000_000_000, large));
+        // x*y=k: 11x the SOL reserve means 121x the spot price
+        assert!(next.abs_diff(121 * price as u128) <= 1);
+    }
+
+    proptest! {
+        #[test]
+        fn bounds_bracket_book_value(
+            liq in 0u128..=(2 * u64::MAX as u128),
+            supply in 1u64..,
+            buf in 0u16..10_000,
+        ) {
+            if let Ok((bv, floor, ceil)) = bounds(liq, supply, buf) {
+                prop_assert!(floor <= bv && bv <= ceil);
+                // floor never overstates, ceil never understates, capital per token
+                let exact = liq * DENOM;
+                prop_assert!(floor as u128 * supply as u128 * BPS <= exact * (BPS - buf as u128));
+                prop_assert!(ceil as u128 * supply as u128 * BPS >= exact * (BPS + buf as u128));
+            }
+        }
+
+        #[test]
+        fn flat_buy_rounds_for_the_pool(lamports_in in any::<u64>(), price in 1u64..) {
+            if let Ok(out) = tokens_out(lamports_in, price) {
+                let paid = lamports_in as u128 * DENOM;
+                prop_assert!(out as u128 * price as u128 <= paid);
+                prop_assert!((out as u128 + 1) * price as u128 > paid);
+            } else {
+                prop_assert!(lamports_in as u128 * DENOM / price as u128 > u64::MAX as u128);
+            }
+        }
+
+        #[test]
+        fn curve_buy_is_never_better_than_spot(
+            lamports_in in any::<u64>(),
+            price in 1u64..,
+            liquidity in 1u64..,
+        ) {
+            if let Ok((out, next)) = buy_on_curve(lamports_in, price, liquidity) {
+                prop_assert!(out <= tokens_out(lamports_in, price).unwrap());
+                prop_assert!(next >= price as u128);
+            }
+        }
+
+        #[test]
+        fn curve_sell_is_never_better_than_spot(
+            amount_in in any::<u64>(),
+            price in 1u64..,
+            liquidity in any::<u64>(),
+        ) {
+            if let Ok((out, next)) = sell_on_curve(amount_in, price, liquidity) {
+                prop_assert!(out as u128 * DENOM <= amount_in as u128 * price as u128);
+                prop_assert!(out <= liquidity);
+                prop_assert!(next <= price as u128);
+            }
+        }
+
+        #[test]
+        fn splitting_a_buy_does_not_help(
+            a in 1u64..1_000_000_000_000,
+            b in 1u64..1_000_000_000_000,
+            price in 1_000u64..1_000_000_000_000,
+            liquidity in 1_000_000_000u64..1_000_000_000_000_000,
+        ) {
+            let (whole, _) = buy_on_curve(a + b, price, liquidity).unwrap();
+            let (first, mid) = buy_on_curve(a, price, liquidity).unwrap();
+            let (second, _) = buy_on_curve(b, spot(mid).unwrap(), liquidity).unwrap();
+            prop_assert!(first + second <= whole + 1, "split={} whole={}", first + second, whole);
+        }
+
+        #[test]
+        fn round_trip_never_profits(
+            lamports_in in 1u64..1_000_000_000_000_000,
+            price in 1u64..1_000_000_000_000,
+            liquidity in 1u64..1_000_000_000_000_000,
+        ) {
+            // Buy and sell back at the same spot: the round trip pays the curve twice
+            if let Ok((tokens, _)) = buy_on_curve(lamports_in, price, liquidity) {
+                let (back, _) = sell_on_curve(tokens, price, liquidity).unwrap();
+                prop_assert!(back <= lamports_in);
+            }
+        }
+
+        #[test]
+        fn curve_never_panics(x in any::<u64>(), price in any::<u64>(), liquidity in any::<u64>()) {
+            let _ = buy_on_curve(x, price, liquidity);
+            let _ = sell_on_curve(x, price, liquidity);
+        }
+    }
+}
@@ -1,16 +1,376 @@
 import * as anchor from "@coral-xyz/anchor";
-import { Program } from "@coral-xyz/anchor";
+import { BN, Program } from "@coral-xyz/anchor";
+import { Keypair, LAMPORTS_PER_SOL, PublicKey } from "@solana/web3.js";
+import {
+  createMint,
+  getAssociatedTokenAddressSync,
+  getOrCreateAssociatedTokenAccount,
+  mintTo,
+} from "@solana/spl-token";
+import { assert } from "chai";
 import { Actuary } from "../target/types/actuary";
 
 describe("actuary", () => {
-  // Configure the client to use the local cluster.
-  anchor.setProvider(anchor.AnchorProvider.env());
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
 
   const program = anchor.workspace.actuary as Program<Actuary>;
+  const connection = provider.connection;
+  const admin = (provider.wallet as anchor.Wallet).payer;
 
-  it("Is initialized!", async () => {
-    // Add your test here.
-    const tx = await program.methods.initialize().rpc();
-    console.log("Your transaction signature", tx);
+  const staker = Keypair.generate();
+  const staker2 = Keypair.generate();
+  const claimant = Keypair.generate();
+  const stranger = Keypair.generate();
+
+  const coverId = new BN(1);
+  const stakeAmount = new BN(500_000_000); // 500 USDC
+  const coverAmount = new BN(100_000_000); // 100 USDC
+
+  const pda = (...seeds: Buffer[]) =>
+    PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+  const config = pda(Buffer.from("config"));
+  const stakeRecOf = (owner: PublicKey) =>
+    pda(Buffer.from("stake"), owner.toBuffer());
+  const stakeRec = stakeRecOf(staker.publicKey);
+  const cover = pda(
+    Buffer.from("cover"),
+    claimant.publicKey.toBuffer(),
+    coverId.toArrayLike(Buffer, "le", 8)
+  );
+  const claim = pda(
+    Buffer.from("claim"),
+    claimant.publicKey.toBuffer(),
+    coverId.toArrayLike(Buffer, "le", 8),
+    new BN(0).toArrayLike(Buffer, "le", 4) // first claim on this cover
+  );
+  const voteRec = (voter: PublicKey) =>
+    pda(Buffer.from("vote"), claim.toBuffer(), voter.toBuffer());
+
+  let usdcMint: PublicKey;
+  let poolVault: PublicKey;
+  let stakerAta: PublicKey;
+  let claimantAta: PublicKey;
+
+  const fundedAta = async (owner: Keypair) => {
+    const ata = await getOrCreateAssociatedTokenAccount(
+      connection,
+      admin,
+      usdcMint,
+      owner.publicKey
+    );
+    await mintTo(connection, admin, usdcMint, ata.address, admin, 1_000_000_000);
+    return ata.address;
+  };
+
+  before(async () => {
+    for (const kp of [staker, staker2, claimant, stranger]) {
+      const sig = await connection.requestAirdrop(kp.publicKey, 2 * LAMPORTS_PER_SOL);
+      await connection.confirmTransaction(sig, "confirmed");
+    }
+
+    usdcMint = await createMint(connection, admin, admin.publicKey, null, 6);
+    poolVault = getAssociatedTokenAddressSync(usdcMint, config, true);
+
+    await program.methods
+      .initialize(admin.publicKey, new BN(0), 10_000, new BN(60), 0)
+      .accountsPartial({ usdcMint, payer: admin.publicKey })
+      .rpc();
+
+    const name = Buffer.alloc(32);
+    name.write("smart-contract");
+    await program.methods
+      .addCoverType(coverId, Array.from(name), {
+        annualRateBps: 500,
+        minDuration: new BN(86_400),
+        maxDuration: new BN(365 * 86_400),
+        minAmount: new BN(1_000_000),
+        maxAmount: new BN(1_000_000_000),
+      })
+      .accountsPartial({ admin: admin.publicKey })
+      .rpc();
+
+    stakerAta = await fundedAta(staker);
+    await program.methods
+      .stake(stakeAmount)
+      .accountsPartial({
+        config,
+        staker: staker.publicKey,
+        stakerAta,
+        usdcMint,
+        poolVault,
+      })
+      .signers([staker])
+      .rpc();
+
+    // A second staker who never votes, so the write-down hits someone passive
+    await program.methods
+      .stake(stakeAmount)
+      .accountsPartial({
+        config,
+        staker: staker2.publicKey,
+        stakerAta: await fundedAta(staker2),
+        usdcMint,
+        poolVault,
+      })
+      .signers([staker2])
+      .rpc();
+
+    claimantAta = await fundedAta(claimant);
+    await program.methods
+      .buyCover(coverId, coverAmount, new BN(30 * 86_400))
+      .accountsPartial({
+        config,
+        claimant: claimant.publicKey,
+        claimantAta,
+        poolVault,
+      })
+      .signers([claimant])
+      .rpc();
+
+    await program.methods
+      .createClaim(coverId, coverAmount, Array.from(Buffer.alloc(32)))
+      .accountsPartial({ config, claimant: claimant.publicKey, cover, claim })
+      .signers([claimant])
+      .rpc();
+  });
+
+  it("rejects a vote cast by a third party for someone else's stake", async () => {
+    try {
+      await program.methods
+        .vote(true, null)
+        .accountsPartial({
+          staker: stranger.publicKey,
+          claim,
+          stakeRec,
+          voteRec: voteRec(stranger.publicKey),
+        })
+        .signers([stranger])
+        .rpc();
+      assert.fail("third party was able to vote");
+    } catch (err) {
+      assert.match(String(err), /ConstraintSeeds|ConstraintHasOne/);
+    }
+  });
+
+  it("rejects a vote that the staker did not sign", async () => {
+    try {
+      await program.methods
+        .vote(true, null)
+        .accountsPartial({
+          staker: staker.publicKey,
+          claim,
+          stakeRec,
+          voteRec: voteRec(staker.publicKey),
+        })
+        .signers([stranger])
+        .rpc();
+      assert.fail("vote went through without the staker's signature");
+    } catch (err) {
+      assert.match(String(err), /[Ss]ignature/);
+    }
+  });
+
+  it("counts a vote signed by the staker", async () => {
+    await program.methods
+      .vote(true, null)
+      .accountsPartial({
+        staker: staker.publicKey,
+        claim,
+        stakeRec,
+        voteRec: voteRec(staker.publicKey),
+      })
+      .signers([staker])
+      .rpc();
+
+    const c = await program.account.claim.fetch(claim);
+    assert.ok(c.yes.eq(stakeAmount));
+    assert.ok(c.no.isZero());
+  });
+
+  it("lets the pauser halt staking and only the admin resume it", async () => {
+    const PAUSE_STAKING = 1;
+    await program.methods
+      .setRoles(admin.publicKey, stranger.publicKey)
+      .accountsPartial({ admin: admin.publicKey })
+      .rpc();
+    await program.methods
+      .pause(PAUSE_STAKING)
+      .accountsPartial({ signer: stranger.publicKey })
+      .signers([stranger])
+      .rpc();
+
+    const stake = () =>
+      program.methods
+        .stake(new BN(1_000_000))
+        .accountsPartial({
+          config,
+          staker: staker.publicKey,
+          stakerAta,
+          usdcMint,
+          poolVault,
+        })
+        .signers([staker])
+        .rpc();
+    try {
+      await stake();
+      assert.fail("staked while paused");
+    } catch (err) {
+      assert.match(String(err), /Paused/);
+    }
+
+    try {
+      await program.methods
+        .unpause(PAUSE_STAKING)
+        .accountsPartial({ admin: stranger.publicKey })
+        .signers([stranger])
+        .rpc();
+      assert.fail("pauser was able to unpause");
+    } catch (err) {
+      assert.match(String(err), /ConstraintHasOne/);
+    }
+
+    await program.methods
+      .unpause(PAUSE_STAKING)
+      .accountsPartial({ admin: admin.publicKey })
+      .rpc();
+    await stake();
+  });
+
+  it("hands admin over only once the proposed key accepts", async () => {
+    await program.methods
+      .proposeAdmin(stranger.publicKey)
+      .accountsPartial({ admin: admin.publicKey })
+      .rpc();
+
+    try {
+      await program.methods
+        .acceptAdmin()
+        .accountsPartial({ pendingAdmin: claimant.publicKey })
+        .signers([claimant])
+        .rpc();
+      assert.fail("non-proposed key accepted admin");
+    } catch (err) {
+      assert.match(String(err), /ConstraintHasOne/);
+    }
+
+    await program.methods
+      .acceptAdmin()
+      .accountsPartial({ pendingAdmin: stranger.publicKey })
+      .signers([stranger])
+      .rpc();
+    let cfg = await program.account.config.fetch(config);
+    assert.ok(cfg.admin.equals(stranger.publicKey));
+    assert.ok(cfg.pendingAdmin.equals(PublicKey.default));
+    // The underwriter role the old admin still held moved with it
+    assert.ok(cfg.underwriter.equals(stranger.publicKey));
+    assert.ok(cfg.pauser.equals(stranger.publicKey));
+
+    // The old admin key can no longer pause, re-price or reassign roles
+    const oldAdminCalls = [
+      program.methods
+        .pause(1)
+        .accountsPartial({ signer: admin.publicKey }),
+      program.methods
+        .updateCoverType(coverId, {
+          annualRateBps: 1,
+          minDuration: new BN(86_400),
+          maxDuration: new BN(365 * 86_400),
+          minAmount: new BN(1_000_000),
+          maxAmount: new BN(1_000_000_000),
+        })
+        .accountsPartial({ signer: admin.publicKey }),
+      program.methods
+        .setRoles(admin.publicKey, admin.publicKey)
+        .accountsPartial({ admin: admin.publicKey }),
+    ];
+    for (const call of oldAdminCalls) {
+      try {
+        await call.rpc();
+        assert.fail("old admin kept a role");
+      } catch (err) {
+        assert.match(String(err), /Unauthorized|ConstraintHasOne/);
+      }
+    }
+
+    // Hand it back so the suite leaves the original admin in place
+    await program.methods
+      .proposeAdmin(admin.publicKey)
+      .accountsPartial({ admin: stranger.publicKey })
+      .signers([stranger])
+      .rpc();
+    await program.methods
+      .acceptAdmin()
+      .accountsPartial({ pendingAdmin: admin.publicKey })
+      .rpc();
+    cfg = await program.account.config.fetch(config);
+    assert.ok(cfg.admin.equals(admin.publicKey));
+    assert.ok(cfg.pauser.equals(admin.publicKey));
+  });
+
+  it("writes every stake down after a payout and still lets all stakers exit", async () => {
+    // Wait out the voting period on the claim the staker voted yes on
+    const c = await program.account.claim.fetch(claim);
+    const wait = c.votingDeadline.toNumber() + 2 - Date.now() / 1000;
+    if (wait > 0) await new Promise((r) => setTimeout(r, wait * 1000));
+
+    const before = await program.account.config.fetch(config);
+    await program.methods
+      .resolve(coverId)
+      .accountsPartial({
+        config,
+        admin: admin.publicKey,
+        claim,
+        cover,
+        poolVault,
+        claimantAta,
+      })
+      .rpc();
+    const after = await program.account.config.fetch(config);
+    assert.ok(after.stakeIndex.lt(before.stakeIndex));
+    // The index rounds down, so the write-down may take one extra unit
+    const expected = before.totalStaked.sub(coverAmount);
+    assert.ok(after.totalStaked.lte(expected));
+    assert.ok(expected.sub(after.totalStaked).lten(1));
+
+    await program.methods
+      .releaseVote()
+      .accountsPartial({
+        staker: staker.publicKey,
+        claim,
+        stakeRec,
+        voteRec: voteRec(staker.publicKey),
+      })
+      .signers([staker])
+      .rpc();
+
+    const precision = new BN(1_000_000_000_000);
+    for (const kp of [staker, staker2]) {
+      const rec = stakeRecOf(kp.publicKey);
+      const { stakeIndex } = await program.account.config.fetch(config);
+      const { shares } = await program.account.stakeRec.fetch(rec);
+      const value = shares.mul(stakeIndex).div(precision);
+      await program.methods
+        .requestUnstake(value)
+        .accountsPartial({ config, staker: kp.publicKey, stakeRec: rec })
+        .signers([kp])
+        .rpc();
+      await program.methods
+        .withdrawStake()
+        .accountsPartial({
+          config,
+          staker: kp.publicKey,
+          stakeRec: rec,
+          stakerAta: getAssociatedTokenAddressSync(usdcMint, kp.publicKey),
+          poolVault,
+        })
+        .signers([kp])
+        .rpc();
+    }
+
+    const cfg = await program.account.config.fetch(config);
+    assert.ok(cfg.totalShares.isZero());
+    assert.ok(cfg.pendingShares.isZero());
+    assert.ok(cfg.totalStaked.isZero());
   });
 });